@@ -0,0 +1,250 @@
+use kaptn_syn::utils::combine_error;
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use sha2::{Digest, Sha256};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Expr, GenericArgument, Ident, ItemStruct, LitInt, Path, PathArguments, Token, Type,
+};
+
+/// Arguments of `#[account(...)]`.
+struct AccountArgs {
+    owner: Option<Expr>,
+    zero_copy: bool,
+}
+
+impl Parse for AccountArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = AccountArgs {
+            owner: None,
+            zero_copy: false,
+        };
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key == "zero_copy" && !args.zero_copy {
+                args.zero_copy = true;
+            } else if key == "owner" && args.owner.is_none() {
+                input.parse::<Token![=]>()?;
+                args.owner = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    key,
+                    "unknown or repeated argument, expected `zero_copy` or `owner = <pubkey>`",
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// Turns a struct into the state of an account owned by the hook program.
+///
+/// The struct is Borsh-serialized behind an 8 byte discriminator, the first
+/// bytes of `sha256("account:<StructName>")`. Accounts are owned by `crate::ID`
+/// unless `#[account(owner = ...)]` is given, and `Vec` and `String` fields
+/// need a `#[max_len(n)]` for the size of the account.
+///
+/// With `#[account(zero_copy)]` the struct is made `Pod` and `repr(C)` instead,
+/// and is stored as its raw bytes behind the discriminator. Its fields must be
+/// `Pod` and leave no padding.
+#[proc_macro_attribute]
+pub fn account(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AccountArgs);
+    let mut item = parse_macro_input!(item as ItemStruct);
+    match generate(args, &mut item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn generate(args: AccountArgs, item: &mut ItemStruct) -> syn::Result<TokenStream2> {
+    if args.zero_copy {
+        return generate_zero_copy(args, item);
+    }
+
+    let mut errors: Option<syn::Error> = None;
+    let mut spaces = vec![];
+    for field in item.fields.iter_mut() {
+        let mut max_len = None;
+        let mut attrs = vec![];
+        for attr in field.attrs.drain(..) {
+            if attr.path.is_ident("max_len") {
+                match attr.parse_args::<LitInt>() {
+                    Ok(lit) => max_len = Some(lit),
+                    Err(err) => combine_error(&mut errors, err),
+                }
+            } else {
+                attrs.push(attr);
+            }
+        }
+        field.attrs = attrs;
+        match field_space(&field.ty, max_len) {
+            Ok(space) => spaces.push(space),
+            Err(err) => combine_error(&mut errors, err),
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let discriminator = discriminator(name);
+    let owner = owner(args.owner);
+
+    Ok(quote! {
+        #[derive(kaptn_lang::borsh::BorshSerialize, kaptn_lang::borsh::BorshDeserialize)]
+        #[borsh(crate = "kaptn_lang::borsh")]
+        #item
+
+        impl #impl_generics kaptn_lang::state::Space for #name #ty_generics #where_clause {
+            const INIT_SPACE: usize = 0 #(+ #spaces)*;
+        }
+
+        impl #impl_generics kaptn_lang::state::State for #name #ty_generics #where_clause {
+            const DISCRIMINATOR: [u8; 8] = [#(#discriminator),*];
+            const OWNER: kaptn_lang::solana_program::pubkey::Pubkey = #owner;
+        }
+    })
+}
+
+fn generate_zero_copy(args: AccountArgs, item: &ItemStruct) -> syn::Result<TokenStream2> {
+    if let Some(attr) = item
+        .fields
+        .iter()
+        .flat_map(|field| &field.attrs)
+        .find(|attr| attr.path.is_ident("max_len"))
+    {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "`max_len` does not apply to zero-copy accounts, use an array",
+        ));
+    }
+
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "zero-copy accounts cannot be generic",
+        ));
+    }
+
+    let name = &item.ident;
+    let discriminator = discriminator(name);
+    let owner = owner(args.owner);
+    let field_tys = item
+        .fields
+        .iter()
+        .map(|field| &field.ty)
+        .collect::<Vec<_>>();
+
+    // `Pod` needs `Copy`, derived here unless the struct already derives it
+    let derived = derived_traits(item)?;
+    let missing = ["Clone", "Copy"]
+        .into_iter()
+        .filter(|name| !derived.iter().any(|derived| derived == name))
+        .map(|name| Ident::new(name, Span::call_site()))
+        .collect::<Vec<_>>();
+    let derive = (!missing.is_empty()).then(|| quote! { #[derive(#(#missing),*)] });
+
+    // `Pod` is implemented here rather than derived, checking the fields are
+    // `Pod` and the struct has no padding like the derive would
+    Ok(quote! {
+        #derive
+        #[repr(C)]
+        #item
+
+        const _: () = {
+            fn assert_pod<T: kaptn_lang::bytemuck::Pod>() {}
+            fn assert_fields() {
+                #(assert_pod::<#field_tys>();)*
+            }
+            assert!(
+                core::mem::size_of::<#name>() == 0 #(+ core::mem::size_of::<#field_tys>())*,
+                "zero-copy accounts cannot have padding, reorder the fields or add explicit padding"
+            );
+        };
+
+        unsafe impl kaptn_lang::bytemuck::Zeroable for #name {}
+        unsafe impl kaptn_lang::bytemuck::Pod for #name {}
+
+        impl kaptn_lang::state::ZeroCopy for #name {
+            const DISCRIMINATOR: [u8; 8] = [#(#discriminator),*];
+            const OWNER: kaptn_lang::solana_program::pubkey::Pubkey = #owner;
+        }
+    })
+}
+
+/// Names of the traits in the `#[derive(...)]` attributes of `item`.
+fn derived_traits(item: &ItemStruct) -> syn::Result<Vec<Ident>> {
+    let mut traits = vec![];
+    for attr in item
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+    {
+        let paths = attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?;
+        traits.extend(
+            paths
+                .into_iter()
+                .filter_map(|path| path.segments.last().map(|segment| segment.ident.clone())),
+        );
+    }
+    Ok(traits)
+}
+
+/// First 8 bytes of `sha256("account:<name>")`.
+fn discriminator(name: &Ident) -> Vec<u8> {
+    Sha256::digest(format!("account:{}", name).as_bytes())[..8].to_vec()
+}
+
+fn owner(owner: Option<Expr>) -> TokenStream2 {
+    match owner {
+        Some(owner) => quote! { #owner },
+        None => quote! { crate::ID_CONST },
+    }
+}
+
+/// Largest size of a field, `Vec` and `String` fields are sized by `max_len`.
+fn field_space(ty: &Type, max_len: Option<LitInt>) -> syn::Result<TokenStream2> {
+    let collection = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last().and_then(|segment| {
+            match (segment.ident.to_string().as_str(), &segment.arguments) {
+                ("String", PathArguments::None) => Some(None),
+                ("Vec", PathArguments::AngleBracketed(args)) => match args.args.first() {
+                    Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(Some(inner)),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }),
+        _ => None,
+    };
+
+    match (collection, max_len) {
+        (Some(None), Some(len)) => Ok(quote! { (4 + #len) }),
+        (Some(Some(inner)), Some(len)) => {
+            if field_space(inner, None).is_err() {
+                return Err(syn::Error::new_spanned(
+                    inner,
+                    "nested `Vec` and `String` fields are not supported",
+                ));
+            }
+            Ok(quote! { (4 + #len * <#inner as kaptn_lang::state::Space>::INIT_SPACE) })
+        }
+        (Some(_), None) => Err(syn::Error::new_spanned(
+            ty,
+            "`Vec` and `String` fields need a `#[max_len(n)]` attribute",
+        )),
+        (None, Some(len)) => Err(syn::Error::new_spanned(
+            len,
+            "`max_len` only applies to `Vec` and `String` fields",
+        )),
+        (None, None) => Ok(quote! { <#ty as kaptn_lang::state::Space>::INIT_SPACE }),
+    }
+}
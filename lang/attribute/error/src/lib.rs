@@ -0,0 +1,214 @@
+use kaptn_syn::utils::combine_error;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, Expr, ExprLit, Fields, Ident, ItemEnum, Lit, LitInt, LitStr, Token,
+};
+
+/// First code of an error enum unless `offset = ...` is given. Codes below it
+/// are left to the runtime and to `KaptnError`.
+const DEFAULT_OFFSET: u32 = 6000;
+
+/// Arguments of `#[kaptn_error(...)]`.
+struct ErrorArgs {
+    offset: u32,
+}
+
+impl Parse for ErrorArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(ErrorArgs {
+                offset: DEFAULT_OFFSET,
+            });
+        }
+        let key: Ident = input.parse()?;
+        if key != "offset" {
+            return Err(syn::Error::new_spanned(
+                key,
+                "unknown argument, expected `offset = <code>`",
+            ));
+        }
+        input.parse::<Token![=]>()?;
+        let offset = input.parse::<LitInt>()?.base10_parse()?;
+        Ok(ErrorArgs { offset })
+    }
+}
+
+/// Turns a fieldless enum into a program error with stable custom codes.
+///
+/// Variants are numbered from 6000, or from `#[kaptn_error(offset = ...)]`,
+/// and can pin their code with an explicit discriminant. `#[msg("...")]` sets
+/// the message that is logged when the error is returned.
+#[proc_macro_attribute]
+pub fn kaptn_error(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as ErrorArgs);
+    let mut item = parse_macro_input!(item as ItemEnum);
+    match generate(args, &mut item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn generate(args: ErrorArgs, item: &mut ItemEnum) -> syn::Result<proc_macro2::TokenStream> {
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "error enums cannot be generic",
+        ));
+    }
+
+    let mut errors: Option<syn::Error> = None;
+    let mut next_code = args.offset;
+    let mut codes: Vec<(u32, Ident)> = vec![];
+    let mut messages = vec![];
+    for variant in item.variants.iter_mut() {
+        if !matches!(variant.fields, Fields::Unit) {
+            combine_error(
+                &mut errors,
+                syn::Error::new_spanned(&variant.fields, "error variants cannot have fields"),
+            );
+        }
+
+        // An explicit discriminant pins the code, the following variants count on from it
+        let code = match variant.discriminant.take() {
+            Some((
+                _,
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(lit), ..
+                }),
+            )) => lit.base10_parse::<u32>()?,
+            Some((_, expr)) => {
+                return Err(syn::Error::new_spanned(
+                    expr,
+                    "expected an integer literal error code",
+                ))
+            }
+            None => next_code,
+        };
+        if let Some((_, other)) = codes.iter().find(|(other, _)| *other == code) {
+            combine_error(
+                &mut errors,
+                syn::Error::new_spanned(
+                    &variant.ident,
+                    format!("error code {} is already used by `{}`", code, other),
+                ),
+            );
+        }
+        next_code = code.saturating_add(1);
+
+        let mut message = None;
+        let mut attrs = vec![];
+        for attr in variant.attrs.drain(..) {
+            if attr.path.is_ident("msg") {
+                match attr.parse_args::<LitStr>() {
+                    Ok(lit) => message = Some(lit.value()),
+                    Err(err) => combine_error(&mut errors, err),
+                }
+            } else {
+                attrs.push(attr);
+            }
+        }
+        variant.attrs = attrs;
+        variant.discriminant = Some((Default::default(), syn::parse_quote! { #code }));
+        messages.push(message.unwrap_or_else(|| variant.ident.to_string()));
+        codes.push((code, variant.ident.clone()));
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let name = &item.ident;
+    let type_name = name.to_string();
+    let variant_codes = codes.iter().map(|(code, _)| code).collect::<Vec<_>>();
+    let variants = codes.iter().map(|(_, ident)| ident).collect::<Vec<_>>();
+    let variant_names = variants
+        .iter()
+        .map(|ident| ident.to_string())
+        .collect::<Vec<_>>();
+
+    Ok(quote! {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        #[repr(u32)]
+        #item
+
+        impl #name {
+            /// Code, name and message of every error, for clients decoding failed transfers.
+            pub const CODES: &'static [(u32, &'static str, &'static str)] = &[
+                #((#variant_codes, #variant_names, #messages),)*
+            ];
+
+            pub fn code(&self) -> u32 {
+                *self as u32
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    #(Self::#variants => #variant_names,)*
+                }
+            }
+
+            pub fn message(&self) -> &'static str {
+                match self {
+                    #(Self::#variants => #messages,)*
+                }
+            }
+
+            pub fn from_code(code: u32) -> Option<Self> {
+                match code {
+                    #(#variant_codes => Some(Self::#variants),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl core::fmt::Display for #name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str(self.message())
+            }
+        }
+
+        impl std::error::Error for #name {}
+
+        impl From<#name> for kaptn_lang::solana_program::program_error::ProgramError {
+            fn from(e: #name) -> Self {
+                kaptn_lang::solana_program::program_error::PrintProgramError::print::<#name>(&e);
+                kaptn_lang::solana_program::program_error::ProgramError::Custom(e.code())
+            }
+        }
+
+        impl<T> kaptn_lang::solana_program::decode_error::DecodeError<T> for #name {
+            fn type_of() -> &'static str {
+                #type_name
+            }
+        }
+
+        impl kaptn_lang::num_traits::FromPrimitive for #name {
+            fn from_i64(n: i64) -> Option<Self> {
+                u32::try_from(n).ok().and_then(Self::from_code)
+            }
+
+            fn from_u64(n: u64) -> Option<Self> {
+                u32::try_from(n).ok().and_then(Self::from_code)
+            }
+        }
+
+        impl kaptn_lang::solana_program::program_error::PrintProgramError for #name {
+            fn print<E>(&self)
+            where
+                E: 'static
+                    + std::error::Error
+                    + kaptn_lang::solana_program::decode_error::DecodeError<E>
+                    + kaptn_lang::solana_program::program_error::PrintProgramError
+                    + kaptn_lang::num_traits::FromPrimitive,
+            {
+                kaptn_lang::solana_program::msg!(
+                    "Error: {} ({}): {}",
+                    self.name(),
+                    self.code(),
+                    self.message()
+                );
+            }
+        }
+    })
+}
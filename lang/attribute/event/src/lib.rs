@@ -0,0 +1,37 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use sha2::{Digest, Sha256};
+use syn::{parse_macro_input, ItemStruct};
+
+/// Turns a struct into an event that can be logged with `emit!`.
+///
+/// The struct is Borsh-serialized behind an 8 byte discriminator, the first
+/// bytes of `sha256("event:<StructName>")`.
+#[proc_macro_attribute]
+pub fn event(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new_spanned(
+            proc_macro2::TokenStream::from(attr),
+            "#[event] takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let item = parse_macro_input!(item as ItemStruct);
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let hash = Sha256::digest(format!("event:{}", name).as_bytes());
+    let discriminator = &hash[..8];
+
+    quote! {
+        #[derive(kaptn_lang::borsh::BorshSerialize, kaptn_lang::borsh::BorshDeserialize)]
+        #[borsh(crate = "kaptn_lang::borsh")]
+        #item
+
+        impl #impl_generics kaptn_lang::event::Event for #name #ty_generics #where_clause {
+            const DISCRIMINATOR: [u8; 8] = [#(#discriminator),*];
+        }
+    }
+    .into()
+}
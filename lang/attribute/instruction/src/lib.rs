@@ -0,0 +1,117 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use sha2::{Digest, Sha256};
+use syn::{parse_macro_input, FnArg, ItemFn, Pat};
+
+/// Turns a function into a custom instruction of the hook program.
+///
+/// The first argument is an `InstructionContext` of the instruction's
+/// `Accounts` struct, the other arguments are Borsh-decoded from the
+/// instruction data after the 8 byte discriminator, the first bytes of
+/// `sha256("instruction:<function_name>")`.
+///
+/// A module with the function's name is generated next to it, holding the
+/// `DISCRIMINATOR`, a `data(...)` builder for clients and the `process`
+/// function the processor dispatches to. List the function in
+/// `#[transfer_hook(instructions(...))]` to have it dispatched.
+#[proc_macro_attribute]
+pub fn instruction(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new_spanned(
+            proc_macro2::TokenStream::from(attr),
+            "#[instruction] takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let item_fn = parse_macro_input!(item as ItemFn);
+    match generate(&item_fn) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn generate(item_fn: &ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &item_fn.sig.ident;
+    let vis = &item_fn.vis;
+    let mut inputs = item_fn.sig.inputs.iter();
+    if inputs.next().is_none() {
+        return Err(syn::Error::new_spanned(
+            &item_fn.sig,
+            "instructions take an `InstructionContext` as their first argument",
+        ));
+    }
+
+    let mut args = vec![];
+    let mut tys = vec![];
+    for input in inputs {
+        match input {
+            FnArg::Typed(arg) => match &*arg.pat {
+                Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none() => {
+                    args.push(pat.ident.clone());
+                    tys.push(&arg.ty);
+                }
+                pat => {
+                    return Err(syn::Error::new_spanned(
+                        pat,
+                        "instruction arguments must be plain identifiers",
+                    ))
+                }
+            },
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new_spanned(
+                    receiver,
+                    "instructions cannot take `self`",
+                ))
+            }
+        }
+    }
+
+    let instruction_name = name.to_string();
+    let hash = Sha256::digest(format!("instruction:{}", instruction_name).as_bytes());
+    let discriminator = &hash[..8];
+    let doc = format!(
+        "Discriminator, arguments and processor of the `{}` instruction.",
+        instruction_name
+    );
+
+    Ok(quote! {
+        #item_fn
+
+        #[doc = #doc]
+        #vis mod #name {
+            use super::*;
+
+            pub const NAME: &str = #instruction_name;
+            pub const DISCRIMINATOR: [u8; 8] = [#(#discriminator),*];
+
+            #[derive(kaptn_lang::borsh::BorshSerialize, kaptn_lang::borsh::BorshDeserialize)]
+            #[borsh(crate = "kaptn_lang::borsh")]
+            pub struct Args {
+                #(pub #args: #tys,)*
+            }
+
+            /// The instruction data for the given arguments.
+            pub fn data(#(#args: #tys),*) -> Vec<u8> {
+                let mut data = DISCRIMINATOR.to_vec();
+                kaptn_lang::borsh::BorshSerialize::serialize(&Args { #(#args),* }, &mut data)
+                    .expect("serializing into a Vec does not fail");
+                data
+            }
+
+            /// Decodes the arguments from the instruction data after the
+            /// discriminator and calls the instruction.
+            pub fn process(
+                program_id: &kaptn_lang::solana_program::pubkey::Pubkey,
+                accounts: &[kaptn_lang::solana_program::account_info::AccountInfo],
+                data: &[u8],
+            ) -> kaptn_lang::solana_program::entrypoint::ProgramResult {
+                let Args { #(#args),* } = kaptn_lang::borsh::from_slice(data).map_err(|_| {
+                    kaptn_lang::solana_program::program_error::ProgramError::InvalidInstructionData
+                })?;
+                let ctx = kaptn_lang::instruction::InstructionContext::new(program_id, accounts)?;
+                super::#name(ctx, #(#args),*)
+            }
+        }
+    })
+}
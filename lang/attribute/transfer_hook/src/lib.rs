@@ -2,7 +2,9 @@ use kaptn_syn::{generate_transfer_hook_code, parse_transfer_hook_input};
 use proc_macro::TokenStream;
 
 #[proc_macro_attribute]
-pub fn transfer_hook(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let input_fn = parse_transfer_hook_input(item.into());
-    TokenStream::from(generate_transfer_hook_code(input_fn))
+pub fn transfer_hook(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match parse_transfer_hook_input(attr.into(), item.into()) {
+        Ok(input) => TokenStream::from(generate_transfer_hook_code(input)),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
@@ -0,0 +1,133 @@
+use kaptn_syn::utils::{combine_error, field_binding, info_lifetime, AttrArg, AttrArgs};
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+/// Implements `Accounts` for a struct, taking one account per field in order.
+///
+/// Every field is loaded with `FromAccountInfo` after the checks of its
+/// `#[accounts(signer, writable, owner = ..., address = ...)]` attribute.
+#[proc_macro_derive(Accounts, attributes(accounts))]
+pub fn derive_accounts(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let (info_lifetime, impl_generics) = info_lifetime(&input.generics);
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data_struct) => &data_struct.fields,
+        _ => {
+            return syn::Error::new_spanned(name, "Accounts can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let mut errors: Option<syn::Error> = None;
+    let mut idents = vec![];
+    let mut loads = vec![];
+    for (i, f) in fields.iter().enumerate() {
+        let (ident, name) = field_binding(i, f);
+        let ty = &f.ty;
+
+        let mut checks = vec![];
+        for attr in f.attrs.iter().filter(|attr| attr.path.is_ident("accounts")) {
+            let args = match attr.parse_args::<AttrArgs>() {
+                Ok(args) => args,
+                Err(err) => {
+                    combine_error(&mut errors, err);
+                    continue;
+                }
+            };
+            for arg in args.0 {
+                match check(&arg, &name) {
+                    Ok(check) => checks.push(check),
+                    Err(err) => combine_error(&mut errors, err),
+                }
+            }
+        }
+
+        loads.push(quote! {
+            let #ident = {
+                let (info, rest) = accounts.split_first().ok_or_else(|| {
+                    msg!("Account `{}` was not passed", #name);
+                    ProgramError::NotEnoughAccountKeys
+                })?;
+                *accounts = rest;
+                #(#checks)*
+                <#ty as FromAccountInfo<#info_lifetime>>::from_account_info(info).map_err(|err| {
+                    msg!("Failed to load account `{}`: {}", #name, err);
+                    err
+                })?
+            };
+        });
+        idents.push(ident);
+    }
+    if let Some(errors) = errors {
+        return errors.to_compile_error().into();
+    }
+
+    let construct = match fields {
+        Fields::Named(_) => quote! { Self { #(#idents),* } },
+        Fields::Unnamed(_) => quote! { Self(#(#idents),*) },
+        Fields::Unit => quote! { Self },
+    };
+
+    quote! {
+        impl #impl_generics Accounts<#info_lifetime> for #name #ty_generics #where_clause {
+            fn try_accounts(
+                accounts: &mut &[AccountInfo<#info_lifetime>],
+            ) -> Result<Self, ProgramError> {
+                #(#loads)*
+                Ok(#construct)
+            }
+        }
+    }
+    .into()
+}
+
+/// The check of a single `#[accounts(...)]` argument, run on `info` before
+/// the field is loaded.
+fn check(arg: &AttrArg, name: &str) -> syn::Result<proc_macro2::TokenStream> {
+    let key = arg.key.to_string();
+    match (key.as_str(), &arg.value) {
+        ("signer", None) => Ok(quote! {
+            if !info.is_signer {
+                msg!("Account `{}` must be a signer", #name);
+                return Err(KaptnError::ConstraintSigner.into());
+            }
+        }),
+        ("writable", None) => Ok(quote! {
+            if !info.is_writable {
+                msg!("Account `{}` must be writable", #name);
+                return Err(KaptnError::ConstraintWritable.into());
+            }
+        }),
+        ("owner", Some(owner)) => Ok(quote! {
+            if *info.owner != #owner {
+                msg!("Constraint `owner` violated by account `{}`", #name);
+                return Err(KaptnError::ConstraintOwner.into());
+            }
+        }),
+        ("address", Some(address)) => Ok(quote! {
+            if *info.key != #address {
+                msg!("Constraint `address` violated by account `{}`", #name);
+                return Err(KaptnError::ConstraintAddress.into());
+            }
+        }),
+        ("signer" | "writable", Some(value)) => Err(syn::Error::new_spanned(
+            value,
+            format!("`{}` takes no value", key),
+        )),
+        ("owner" | "address", None) => Err(syn::Error::new_spanned(
+            &arg.key,
+            format!("`{}` needs a value, e.g. `{} = <pubkey>`", key, key),
+        )),
+        _ => Err(syn::Error::new_spanned(
+            &arg.key,
+            "unknown argument, expected `signer`, `writable`, `owner = ...` or `address = ...`",
+        )),
+    }
+}
@@ -0,0 +1,125 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, Ident};
+
+use crate::seeds::BASE_ACCOUNT_NAMES;
+
+/// A check on an extra meta, run after all fields are loaded and before the
+/// transfer hook is called.
+pub enum Constraint {
+    /// `owner = expr`: the account is owned by the given program.
+    Owner(Expr),
+    /// `address = expr`: the account has the given address.
+    Address(Expr),
+    /// `has_one = target`: the account's `target` field is the key of the
+    /// base account or sibling field named `target`.
+    HasOne(Ident),
+    /// `constraint = expr`: an arbitrary boolean expression over the fields.
+    Raw(Expr),
+}
+
+impl Constraint {
+    /// Parses a `key = value` meta argument, if it is a constraint.
+    pub fn parse(key: &Ident, value: &Expr) -> Option<syn::Result<Self>> {
+        let constraint = match key.to_string().as_str() {
+            "owner" => Ok(Constraint::Owner(value.clone())),
+            "address" => Ok(Constraint::Address(value.clone())),
+            "has_one" => match value {
+                Expr::Path(path) => path
+                    .path
+                    .get_ident()
+                    .cloned()
+                    .map(Constraint::HasOne)
+                    .ok_or_else(|| {
+                        syn::Error::new_spanned(value, "expected the name of an account")
+                    }),
+                _ => Err(syn::Error::new_spanned(
+                    value,
+                    "expected the name of an account",
+                )),
+            },
+            "constraint" => Ok(Constraint::Raw(value.clone())),
+            _ => return None,
+        };
+        Some(constraint)
+    }
+
+    /// Generates the check for the field `name`, loaded into the variable
+    /// `ident`, where `accounts` are the names of the fields that hold an account
+    /// and `optional` those of them that are optional metas.
+    pub fn generate(
+        &self,
+        ident: &Ident,
+        name: &str,
+        accounts: &[String],
+        optional: &[String],
+    ) -> syn::Result<TokenStream> {
+        let info = quote! { AsRef::<AccountInfo>::as_ref(&#ident) };
+        let (kind, check, error) = match self {
+            Constraint::Owner(owner) => (
+                "owner",
+                quote! { *#info.owner == #owner },
+                quote! { KaptnError::ConstraintOwner },
+            ),
+            Constraint::Address(address) => (
+                "address",
+                quote! { *#info.key == #address },
+                quote! { KaptnError::ConstraintAddress },
+            ),
+            Constraint::HasOne(target) => {
+                let target_name = target.to_string();
+                let target_key = if let Some(index) = BASE_ACCOUNT_NAMES
+                    .iter()
+                    .position(|base| *base == target_name)
+                {
+                    quote! {
+                        *__accounts.get(#index).ok_or(ProgramError::NotEnoughAccountKeys)?.key
+                    }
+                } else if optional.contains(&target_name) {
+                    // Without the target account the relation cannot be checked
+                    quote! {
+                        match &#target {
+                            Some(#target) => *AsRef::<AccountInfo>::as_ref(#target).key,
+                            None => {
+                                msg!(
+                                    "Constraint `has_one` of extra meta `{}` needs `{}`, which was not passed",
+                                    #name,
+                                    #target_name
+                                );
+                                return Err(KaptnError::ConstraintHasOne.into());
+                            }
+                        }
+                    }
+                } else if accounts.contains(&target_name) {
+                    quote! { *AsRef::<AccountInfo>::as_ref(&#target).key }
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        target,
+                        format!(
+                            "unknown account `{}`, expected one of {} or an extra meta field",
+                            target_name,
+                            BASE_ACCOUNT_NAMES.join(", ")
+                        ),
+                    ));
+                };
+                (
+                    "has_one",
+                    quote! { #ident.#target == #target_key },
+                    quote! { KaptnError::ConstraintHasOne },
+                )
+            }
+            Constraint::Raw(expr) => (
+                "constraint",
+                quote! { #expr },
+                quote! { KaptnError::ConstraintRaw },
+            ),
+        };
+
+        Ok(quote! {
+            if !(#check) {
+                msg!("Constraint `{}` violated by extra meta `{}`", #kind, #name);
+                return Err(#error.into());
+            }
+        })
+    }
+}
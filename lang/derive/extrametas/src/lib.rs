@@ -1,20 +1,34 @@
+use kaptn_syn::utils::{combine_error, field_binding, info_lifetime, AttrArgs};
 use proc_macro::TokenStream;
-use quote::{quote, ToTokens};
+use proc_macro2::{Span, TokenTree};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
-    parse::{Parse, ParseStream},
-    parse_macro_input,
-    punctuated::Punctuated,
-    Attribute, Data, DeriveInput, Expr, Fields, GenericParam, Lit, Meta, NestedMeta, Token,
+    parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Lit, LitByte, Type,
 };
 
-struct Seeds(Punctuated<Expr, Token![,]>);
+mod constraints;
+mod seeds;
 
-impl Parse for Seeds {
-    fn parse(input: ParseStream) -> syn::Result<Self> {
-        let content;
-        syn::bracketed!(content in input);
-        Ok(Seeds(content.parse_terminated(Expr::parse)?))
-    }
+use constraints::Constraint;
+use seeds::Preceding;
+
+/// How a field of the struct is filled in.
+enum FieldMeta {
+    /// An extra account meta, taken from the accounts list and checked
+    /// against its constraints.
+    Account {
+        meta: proc_macro2::TokenStream,
+        /// Whether the meta is a PDA of the hook program, which it can sign for.
+        hook_pda: bool,
+        /// Whether its seeds refer to an extra meta by raw index.
+        raw_index: bool,
+        constraints: Vec<Constraint>,
+    },
+    /// Another `ExtraMetas` struct embedded with `#[meta(flatten)]`, its metas
+    /// are spliced in starting at the account index `offset`.
+    Flatten { offset: proc_macro2::TokenStream },
+    /// Left out of the extra metas with `#[meta(skip)]`, set to `Default::default()`.
+    Skip,
 }
 
 #[proc_macro_derive(ExtraMetas, attributes(meta))]
@@ -22,188 +36,563 @@ pub fn derive_extra_metas(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
 
-    // Extract lifetimes
-    let lifetimes: Vec<_> = input
-        .generics
-        .params
-        .iter()
-        .filter_map(|param| {
-            if let GenericParam::Lifetime(lt) = param {
-                Some(lt.lifetime.clone())
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    // Determine if the 'info lifetime is present
-    let has_info_lifetime = lifetimes.iter().any(|lt| lt.to_string() == "'info");
+    let (info_lifetime, impl_generics) = info_lifetime(&input.generics);
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
 
     // Extract fields
-    let fields = if let Data::Struct(data_struct) = &input.data {
-        if let Fields::Named(fields) = &data_struct.fields {
-            fields.named.iter().collect::<Vec<_>>()
-        } else {
-            vec![]
+    let fields = match &input.data {
+        Data::Struct(data_struct) => &data_struct.fields,
+        _ => {
+            return syn::Error::new_spanned(name, "ExtraMetas can only be derived for structs")
+                .to_compile_error()
+                .into()
         }
-    } else {
-        vec![]
     };
 
-    // Process account metas: pubkey or seeds
-    let account_metas = fields
+    // Process account metas: pubkey or seeds, flattened structs or skipped fields
+    let mut errors: Option<syn::Error> = None;
+    let mut field_metas = vec![];
+    let mut preceding: Vec<Preceding> = vec![];
+    let mut account_count = 0;
+    let mut flattened: Vec<&Type> = vec![];
+    let mut optional_seen = false;
+    let mut signer_seeds_accessors = vec![];
+    for (i, f) in fields.iter().enumerate() {
+        let (ident, name) = field_binding(i, f);
+        let span: &dyn ToTokens = match &f.ident {
+            Some(ident) => ident,
+            None => &f.ty,
+        };
+        let mut meta_attrs = f.attrs.iter().filter(|attr| attr.path.is_ident("meta"));
+        let result = match (meta_attrs.next(), meta_attrs.next()) {
+            (Some(attr), None) => parse_meta_attribute(attr, &preceding),
+            (Some(_), Some(duplicate)) => Err(syn::Error::new_spanned(
+                duplicate,
+                "duplicate #[meta] attribute",
+            )),
+            (None, _) => Err(syn::Error::new_spanned(
+                span,
+                format!(
+                    "field `{}` needs a #[meta(...)] attribute, or #[meta(skip)] to leave it out of the extra metas",
+                    name
+                ),
+            )),
+        };
+        let index = meta_index(account_count, &flattened);
+        match result {
+            Ok(FieldMeta::Skip) => field_metas.push((ident, name, &f.ty, FieldMeta::Skip)),
+            Ok(FieldMeta::Flatten { .. }) => {
+                if optional_seen {
+                    combine_error(
+                        &mut errors,
+                        syn::Error::new_spanned(
+                            span,
+                            format!(
+                                "flattened extra metas `{}` cannot come after an optional one",
+                                name
+                            ),
+                        ),
+                    );
+                }
+                preceding.push(Preceding {
+                    name: name.clone(),
+                    index: None,
+                });
+                flattened.push(&f.ty);
+                field_metas.push((ident, name, &f.ty, FieldMeta::Flatten { offset: index }));
+            }
+            Ok(meta) => {
+                // Optional metas can only be left out at the end of the accounts list
+                if is_option(&f.ty) {
+                    optional_seen = true;
+                } else if optional_seen {
+                    combine_error(
+                        &mut errors,
+                        syn::Error::new_spanned(
+                            span,
+                            format!(
+                                "required extra meta `{}` cannot come after an optional one",
+                                name
+                            ),
+                        ),
+                    );
+                }
+                if let (FieldMeta::Account { hook_pda: true, .. }, Some(field)) = (&meta, &f.ident)
+                {
+                    let accessor = format_ident!("{}_signer_seeds", field);
+                    let doc = format!(
+                        "The seeds and bump of `{}`, for signing as that PDA of the hook program.",
+                        name
+                    );
+                    signer_seeds_accessors.push(quote! {
+                        #[doc = #doc]
+                        pub fn #accessor(
+                            ctx: &TransferContext<'_, #info_lifetime, Self>,
+                        ) -> Result<SignerSeeds, ProgramError> {
+                            let __offset: u8 = 5;
+                            ctx.signer_seeds((#index) as usize)
+                        }
+                    });
+                }
+                preceding.push(Preceding {
+                    name: name.clone(),
+                    index: Some(index),
+                });
+                account_count += 1;
+                field_metas.push((ident, name, &f.ty, meta));
+            }
+            Err(err) => {
+                // Keep the name so later seeds referring to it don't error as well
+                preceding.push(Preceding {
+                    name,
+                    index: Some(index),
+                });
+                account_count += 1;
+                combine_error(&mut errors, err);
+            }
+        }
+    }
+    if let Some(errors) = errors {
+        return errors.to_compile_error().into();
+    }
+
+    let account_metas = field_metas
         .iter()
-        .filter_map(|f| {
-            let ident = f.ident.as_ref()?;
-            let meta_attr = f.attrs.iter().find(|attr| attr.path.is_ident("meta"))?;
-            parse_meta_attribute(ident, meta_attr).ok()
+        .filter_map(|(_, _, ty, meta)| match meta {
+            FieldMeta::Account { meta, .. } => Some(quote! {
+                __metas.push(#meta);
+            }),
+            FieldMeta::Flatten { offset } => Some(quote! {
+                __metas.extend(<#ty as ExtraMetas>::to_extra_account_metas_at(#offset));
+            }),
+            FieldMeta::Skip => None,
         })
         .collect::<Vec<_>>();
-
-    let field_names = fields
+    let meta_fields = field_metas
         .iter()
-        .filter_map(|f| f.ident.as_ref())
+        .filter_map(|(_, name, ty, meta)| match meta {
+            FieldMeta::Account { .. } => {
+                let optional = is_option(ty);
+                Some(quote! {
+                    __fields.push(MetaField { name: #name.to_string(), optional: #optional });
+                })
+            }
+            FieldMeta::Flatten { .. } => Some(quote! {
+                __fields.extend(<#ty as ExtraMetas>::meta_fields().into_iter().map(|field| {
+                    MetaField {
+                        name: format!("{}.{}", #name, field.name),
+                        ..field
+                    }
+                }));
+            }),
+            FieldMeta::Skip => None,
+        })
         .collect::<Vec<_>>();
+    let account_count = proc_macro2::Literal::usize_unsuffixed(account_count);
+    let len = quote! { #account_count #(+ <#flattened as ExtraMetas>::LEN)* };
 
-    // Generate from_accounts method
-    let from_accounts = if has_info_lifetime {
-        quote! {
-            fn from_accounts(accounts: &[AccountInfo<'info>]) -> Result<Self, ProgramError> {
-                let mut iter = accounts.iter().skip(5); // Adjust skip as needed
-                Ok(Self {
-                    #(#field_names: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?.clone(),)*
-                })
+    // Structs referring to their own metas by raw index only work at the top level.
+    // The check is a free constant when the flattened types are concrete, so it
+    // fails `cargo check`, and part of `LEN` otherwise.
+    let flattenable = !field_metas.iter().any(|(_, _, _, meta)| {
+        matches!(
+            meta,
+            FieldMeta::Account {
+                raw_index: true,
+                ..
             }
-        }
-    } else {
-        quote! {
-            fn from_accounts(accounts: &[AccountInfo]) -> Result<Self, ProgramError> {
-                let mut iter = accounts.iter().skip(5); // Skip till the extra account metas
-                Ok(Self {
-                    #(#field_names: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?.clone(),)*
-                })
+        )
+    });
+    let flatten_checks = flattened
+        .iter()
+        .map(|ty| {
+            let message = format!(
+                "`{}` refers to its extra metas by raw index and cannot be flattened, refer to its fields by name instead",
+                quote!(#ty).to_string().replace(' ', "")
+            );
+            if input.generics.type_params().next().is_none() {
+                let ty = static_lifetimes(quote!(#ty));
+                quote! {
+                    const _: () = assert!(<#ty as ExtraMetas<'static>>::FLATTENABLE, #message);
+                }
+            } else {
+                quote! {
+                    assert!(<#ty as ExtraMetas>::FLATTENABLE, #message);
+                }
             }
-        }
+        })
+        .collect::<Vec<_>>();
+    let (free_flatten_checks, len) = if input.generics.type_params().next().is_none() {
+        (flatten_checks, len)
+    } else {
+        (vec![], quote! {{ #(#flatten_checks)* #len }})
     };
 
-    // Generate trait implementation for ExtraMetas
-    let gen = if has_info_lifetime {
-        quote! {
-            impl<#(#lifetimes),*> ExtraMetas<#(#lifetimes),*> for #name<#(#lifetimes),*> {
-                #from_accounts
-
-                fn to_extra_account_metas() -> Vec<ExtraAccountMeta> {
-                    vec![
-                        #(#account_metas),*
-                    ]
+    let field_loads = field_metas
+        .iter()
+        .map(|(ident, name, ty, meta)| match meta {
+            FieldMeta::Account { meta, .. } => {
+                let load = quote! {
+                    FromAccountInfo::from_account_info(info).map_err(|err| {
+                        msg!("Failed to load extra meta `{}`: {}", #name, err);
+                        err
+                    })?
+                };
+                if is_option(ty) {
+                    quote! {
+                        let #ident: #ty = match __resolver.next_optional(#name, &#meta)? {
+                            Some(info) => Some(#load),
+                            None => None,
+                        };
+                    }
+                } else {
+                    quote! {
+                        let #ident: #ty = {
+                            let info = __resolver.next(#name, &#meta)?;
+                            #load
+                        };
+                    }
                 }
             }
-        }
-    } else if !lifetimes.is_empty() {
-        quote! {
-            impl<#(#lifetimes),*> ExtraMetas<'_, #(#lifetimes),*> for #name<#(#lifetimes),*> {
-                #from_accounts
-
-                fn to_extra_account_metas() -> Vec<ExtraAccountMeta> {
-                    vec![
-                        #(#account_metas),*
-                    ]
+            FieldMeta::Flatten { .. } => quote! {
+                let #ident: #ty = ExtraMetas::from_resolver(__resolver)?;
+            },
+            FieldMeta::Skip => quote! {
+                let #ident: #ty = Default::default();
+            },
+        })
+        .collect::<Vec<_>>();
+
+    // Constraints run once every field is loaded, so they can refer to any of them
+    let account_names = preceding
+        .iter()
+        .filter(|field| field.index.is_some())
+        .map(|field| field.name.clone())
+        .collect::<Vec<_>>();
+    let optional_names = field_metas
+        .iter()
+        .filter(|(_, _, ty, meta)| matches!(meta, FieldMeta::Account { .. }) && is_option(ty))
+        .map(|(_, name, _, _)| name.clone())
+        .collect::<Vec<_>>();
+    let mut constraint_checks = vec![];
+    for (ident, name, ty, meta) in field_metas.iter() {
+        if let FieldMeta::Account { constraints, .. } = meta {
+            let mut checks = vec![];
+            for constraint in constraints {
+                match constraint.generate(ident, name, &account_names, &optional_names) {
+                    Ok(check) => checks.push(check),
+                    Err(err) => combine_error(&mut errors, err),
                 }
             }
-        }
-    } else {
-        quote! {
-            impl ExtraMetas<'_> for #name {
-                #from_accounts
-
-                fn to_extra_account_metas() -> Vec<ExtraAccountMeta> {
-                    vec![
-                        #(#account_metas),*
-                    ]
+            // Constraints on optional metas only apply when the account was passed
+            constraint_checks.push(if is_option(ty) && !checks.is_empty() {
+                quote! {
+                    if let Some(#ident) = &#ident {
+                        #(#checks)*
+                    }
                 }
-            }
+            } else {
+                quote! { #(#checks)* }
+            });
         }
+    }
+    if let Some(errors) = errors {
+        return errors.to_compile_error().into();
+    }
+
+    let field_idents = field_metas.iter().map(|(ident, _, _, _)| ident);
+    let construct = match fields {
+        Fields::Named(_) => quote! { Self { #(#field_idents,)* } },
+        Fields::Unnamed(_) => quote! { Self(#(#field_idents),*) },
+        Fields::Unit => quote! { Self },
     };
 
-    gen.into()
-}
+    // Generate trait implementation for ExtraMetas
+    let gen = quote! {
+        impl #impl_generics ExtraMetas<#info_lifetime> for #name #ty_generics #where_clause {
+            const LEN: usize = #len;
+            const FLATTENABLE: bool = #flattenable;
 
-fn parse_meta_attribute(
-    _ident: &syn::Ident,
-    attr: &Attribute,
-) -> syn::Result<proc_macro2::TokenStream> {
-    let meta = attr.parse_meta()?;
-    if let Meta::List(list) = meta {
-        let mut pubkey = None;
-        let mut signer = false;
-        let mut writable = false;
-        let mut seeds = None;
-
-        for nested in list.nested.iter() {
-            match nested {
-                NestedMeta::Meta(Meta::NameValue(nv)) => {
-                    match nv.path.get_ident().map(|i| i.to_string()).as_deref() {
-                        Some("pubkey") => {
-                            if let Lit::Str(lit) = &nv.lit {
-                                pubkey = Some(lit.value());
-                            }
-                        }
-                        Some("signer") => {
-                            if let Lit::Bool(lit) = &nv.lit {
-                                signer = lit.value();
-                            }
-                        }
-                        Some("writable") => {
-                            if let Lit::Bool(lit) = &nv.lit {
-                                writable = lit.value();
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("seeds") => {
-                    seeds = Some(syn::parse2::<Seeds>(list.nested.to_token_stream())?);
-                }
-                _ => {}
+            fn from_resolver(
+                __resolver: &mut MetaResolver<'_, #info_lifetime>,
+            ) -> Result<Self, ProgramError> {
+                // Seeds refer to the struct's own fields relative to its first meta
+                let __offset = __resolver.position() as u8;
+                let __accounts = __resolver.accounts();
+                #(#field_loads)*
+                #(#constraint_checks)*
+                Ok(#construct)
+            }
+
+            fn to_extra_account_metas_at(__offset: u8) -> Vec<ExtraAccountMeta> {
+                let mut __metas = Vec::with_capacity(Self::LEN);
+                #(#account_metas)*
+                __metas
+            }
+
+            fn meta_fields() -> Vec<MetaField> {
+                let mut __fields = Vec::with_capacity(Self::LEN);
+                #(#meta_fields)*
+                __fields
             }
         }
 
-        if pubkey.is_some() && seeds.is_some() {
-            return Err(syn::Error::new_spanned(
-                attr,
-                "Cannot specify both pubkey and seeds",
-            ));
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#signer_seeds_accessors)*
         }
 
-        if pubkey.is_none() && seeds.is_none() {
+        #(#free_flatten_checks)*
+    };
+
+    gen.into()
+}
+
+fn parse_meta_attribute(attr: &Attribute, preceding: &[Preceding]) -> syn::Result<FieldMeta> {
+    let args = attr.parse_args::<AttrArgs>()?;
+
+    if let Some(arg) = args
+        .0
+        .iter()
+        .find(|arg| arg.key == "skip" || arg.key == "flatten")
+    {
+        if args.0.len() > 1 || arg.value.is_some() {
             return Err(syn::Error::new_spanned(
                 attr,
-                "Must specify either pubkey or seeds",
+                format!(
+                    "#[meta({})] cannot be combined with other arguments",
+                    arg.key
+                ),
             ));
         }
+        return Ok(if arg.key == "skip" {
+            FieldMeta::Skip
+        } else {
+            // The offset depends on the field's position, the caller fills it in
+            FieldMeta::Flatten {
+                offset: proc_macro2::TokenStream::new(),
+            }
+        });
+    }
 
-        // Generate appropriate ExtraAccountMeta based on pubkey or seeds
-        Ok(if let Some(pk) = pubkey {
-            quote! {
-                ExtraAccountMeta::new_with_pubkey(&#pk.parse().unwrap(), #signer, #writable).unwrap()
+    let mut errors: Option<syn::Error> = None;
+    let mut pubkey = None;
+    let mut signer = None;
+    let mut writable = None;
+    let mut seeds = None;
+    let mut program = None;
+    let mut raw_index = false;
+    let mut constraints = vec![];
+
+    for arg in args.0.iter() {
+        let value = match &arg.value {
+            Some(value) => value,
+            None => {
+                combine_error(
+                    &mut errors,
+                    syn::Error::new_spanned(&arg.key, format!("expected `{} = ...`", arg.key)),
+                );
+                continue;
+            }
+        };
+        if let Some(constraint) = Constraint::parse(&arg.key, value) {
+            match constraint {
+                Ok(constraint) => constraints.push(constraint),
+                Err(err) => combine_error(&mut errors, err),
             }
-        } else if let Some(Seeds(seeds)) = seeds {
-            let seed_exprs = seeds.iter();
-            quote! {
+            continue;
+        }
+        let result = match arg.key.to_string().as_str() {
+            "pubkey" => parse_pubkey(value).map(|pk| pubkey = Some(pk)),
+            "signer" => flag(value).map(|value| signer = value),
+            "writable" => flag(value).map(|value| writable = value),
+            "seeds" => {
+                raw_index |= seeds::uses_raw_meta_index(value);
+                seeds::parse_seeds(value, preceding).map(|value| seeds = Some(value))
+            }
+            "program" => {
+                raw_index |= seeds::is_raw_meta_index(value);
+                seeds::account_index(value, preceding).map(|index| program = Some((value, index)))
+            }
+            _ => Err(syn::Error::new_spanned(
+                &arg.key,
+                format!("unknown meta argument `{}`", arg.key),
+            )),
+        };
+        if let Err(err) = result {
+            combine_error(&mut errors, err);
+        }
+    }
+
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    if pubkey.is_some() && seeds.is_some() {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "Cannot specify both pubkey and seeds",
+        ));
+    }
+
+    if let (Some((program, _)), None) = (&program, &seeds) {
+        return Err(syn::Error::new_spanned(
+            program,
+            "`program` can only be used together with seeds",
+        ));
+    }
+
+    // Generate appropriate ExtraAccountMeta based on pubkey or seeds
+    let (is_signer, is_writable) = (flag_tokens(&signer), flag_tokens(&writable));
+    if let Some(pk) = pubkey {
+        Ok(FieldMeta::Account {
+            meta: quote! {
+                ExtraAccountMeta::new_with_pubkey(&#pk, #is_signer, #is_writable).unwrap()
+            },
+            hook_pda: false,
+            raw_index,
+            constraints,
+        })
+    } else if let Some(seeds) = seeds {
+        let seed_exprs = seeds.iter().map(seeds::SeedSpec::to_tokens);
+        let hook_pda = program.is_none();
+        let meta = match program {
+            // PDA of another program that is already in the accounts list
+            Some((_, program_index)) => quote! {
                 ExtraAccountMeta::new_external_pda_with_seeds(
-                    0, // Associated token program index, adjust as needed
+                    #program_index,
                     &[#(#seed_exprs),*],
-                    #signer,
-                    #writable
+                    #is_signer,
+                    #is_writable
                 ).unwrap()
-            }
-        } else {
-            unreachable!()
+            },
+            // PDA of the transfer-hook program itself
+            None => quote! {
+                ExtraAccountMeta::new_with_seeds(
+                    &[#(#seed_exprs),*],
+                    #is_signer,
+                    #is_writable
+                ).unwrap()
+            },
+        };
+        Ok(FieldMeta::Account {
+            meta,
+            hook_pda,
+            raw_index,
+            constraints,
         })
     } else {
         Err(syn::Error::new_spanned(
             attr,
-            "Expected list-style attribute",
+            "Must specify either pubkey or seeds",
         ))
     }
 }
+
+/// Expression for the account index of a meta that follows `accounts` metas
+/// and the `flattened` structs, relative to the struct's first meta at `__offset`.
+///
+/// The size of a flattened struct is only known once its own derive has run,
+/// so it is added through `ExtraMetas::LEN`.
+fn meta_index(accounts: usize, flattened: &[&Type]) -> proc_macro2::TokenStream {
+    let accounts = proc_macro2::Literal::usize_unsuffixed(accounts);
+    if flattened.is_empty() {
+        quote! { __offset + #accounts }
+    } else {
+        quote! { (__offset as usize + #accounts #(+ <#flattened as ExtraMetas>::LEN)*) as u8 }
+    }
+}
+
+/// Replaces every lifetime in `tokens` with `'static`.
+fn static_lifetimes(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let mut lifetime = false;
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(group) => {
+                let mut replaced =
+                    proc_macro2::Group::new(group.delimiter(), static_lifetimes(group.stream()));
+                replaced.set_span(group.span());
+                TokenTree::Group(replaced)
+            }
+            TokenTree::Punct(punct) => {
+                lifetime = punct.as_char() == '\'';
+                TokenTree::Punct(punct)
+            }
+            TokenTree::Ident(ident) if lifetime => {
+                lifetime = false;
+                TokenTree::Ident(proc_macro2::Ident::new("static", ident.span()))
+            }
+            token => {
+                lifetime = false;
+                token
+            }
+        })
+        .collect()
+}
+
+/// Whether a field is an `Option<...>`, i.e. an optional extra meta.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Decodes a base58 pubkey literal at expansion time, or passes a path to a
+/// `Pubkey` constant through as is.
+fn parse_pubkey(expr: &Expr) -> syn::Result<proc_macro2::TokenStream> {
+    let id_literal = match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => lit,
+        Expr::Path(path) => return Ok(quote! { #path }),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                expr,
+                "expected a base58 string literal or a path to a `Pubkey` constant",
+            ))
+        }
+    };
+
+    let id_vec = bs58::decode(id_literal.value())
+        .into_vec()
+        .map_err(|_| syn::Error::new_spanned(id_literal, "failed to decode base58 string"))?;
+    let id_array = <[u8; 32]>::try_from(id_vec.as_slice()).map_err(|_| {
+        syn::Error::new_spanned(
+            id_literal,
+            format!("pubkey array is not 32 bytes long: len={}", id_vec.len()),
+        )
+    })?;
+    let bytes = id_array.iter().map(|b| LitByte::new(*b, Span::call_site()));
+    Ok(quote! {
+        Pubkey::new_from_array([#(#bytes,)*])
+    })
+}
+
+/// Parses a `signer` or `writable` flag, either a bool literal or a const
+/// expression. Returns `None` when the flag is statically `false`.
+fn flag(expr: &Expr) -> syn::Result<Option<Box<Expr>>> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Bool(lit),
+            ..
+        }) => Ok(lit.value().then(|| Box::new(expr.clone()))),
+        Expr::Lit(_) => Err(syn::Error::new_spanned(
+            expr,
+            "expected `true`, `false` or a const bool expression",
+        )),
+        _ => Ok(Some(Box::new(expr.clone()))),
+    }
+}
+
+fn flag_tokens(flag: &Option<Box<Expr>>) -> proc_macro2::TokenStream {
+    match flag {
+        Some(expr) => quote! { #expr },
+        None => quote! { false },
+    }
+}
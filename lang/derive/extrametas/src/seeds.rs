@@ -0,0 +1,362 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, ExprCall, Lit};
+
+/// Number of accounts in the `Execute` instruction before the extra metas.
+pub const BASE_ACCOUNTS: usize = 5;
+
+/// Names of the accounts in the `Execute` instruction, in order.
+pub const BASE_ACCOUNT_NAMES: [&str; BASE_ACCOUNTS] = [
+    "source",
+    "mint",
+    "destination",
+    "authority",
+    "extra_account_metas",
+];
+
+/// Length of the `Execute` instruction data: 8 byte discriminator and a u64 amount.
+const EXECUTE_DATA_LEN: usize = 16;
+
+/// Offsets of the fields of a token account that can be used as seeds with
+/// `mint_of`, `owner_of` and `delegate_of`. The delegate is a `COption`, its key
+/// follows the 4 byte tag.
+const TOKEN_ACCOUNT_MINT_OFFSET: u8 = 0;
+const TOKEN_ACCOUNT_OWNER_OFFSET: u8 = 32;
+const TOKEN_ACCOUNT_DELEGATE_OFFSET: u8 = 76;
+
+/// Seed configurations are packed into the 32 byte `address_config`.
+const MAX_SEED_CONFIG_LEN: usize = 32;
+
+/// An extra meta field declared before the one being parsed.
+pub struct Preceding {
+    pub name: String,
+    /// Expression for the field's account index, `None` for a flattened struct.
+    pub index: Option<TokenStream>,
+}
+
+/// A seed as written in `#[meta(seeds = [...])]`, validated at expansion time.
+pub enum SeedSpec {
+    Literal {
+        bytes: Vec<u8>,
+    },
+    InstructionData {
+        index: u8,
+        length: u8,
+    },
+    AccountKey {
+        index: TokenStream,
+    },
+    AccountData {
+        account_index: TokenStream,
+        data_index: u8,
+        length: u8,
+    },
+}
+
+impl SeedSpec {
+    /// Packed size of the seed configuration, mirroring `Seed::tlv_size`.
+    fn tlv_size(&self) -> usize {
+        match self {
+            SeedSpec::Literal { bytes } => 1 + 1 + bytes.len(),
+            SeedSpec::InstructionData { .. } => 1 + 1 + 1,
+            SeedSpec::AccountKey { .. } => 1 + 1,
+            SeedSpec::AccountData { .. } => 1 + 1 + 1 + 1,
+        }
+    }
+
+    pub fn to_tokens(&self) -> TokenStream {
+        match self {
+            SeedSpec::Literal { bytes } => quote! {
+                Seed::Literal { bytes: vec![#(#bytes),*] }
+            },
+            SeedSpec::InstructionData { index, length } => quote! {
+                Seed::InstructionData { index: #index, length: #length }
+            },
+            SeedSpec::AccountKey { index } => quote! {
+                Seed::AccountKey { index: #index }
+            },
+            SeedSpec::AccountData {
+                account_index,
+                data_index,
+                length,
+            } => quote! {
+                Seed::AccountData {
+                    account_index: #account_index,
+                    data_index: #data_index,
+                    length: #length,
+                }
+            },
+        }
+    }
+}
+
+/// Parses the `seeds = [...]` array of a meta, given the extra meta fields
+/// declared before it.
+///
+/// Accounts may only be referenced if they are resolved before this meta, i.e.
+/// one of the base accounts or an earlier extra meta.
+pub fn parse_seeds(expr: &Expr, preceding: &[Preceding]) -> syn::Result<Vec<SeedSpec>> {
+    let array = match expr {
+        Expr::Array(array) => array,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                expr,
+                "expected an array of seeds, e.g. `seeds = [literal(b\"counter\"), account_key(source)]`",
+            ))
+        }
+    };
+
+    if array.elems.is_empty() {
+        return Err(syn::Error::new_spanned(
+            expr,
+            "a PDA needs at least one seed",
+        ));
+    }
+
+    let seeds = array
+        .elems
+        .iter()
+        .map(|elem| parse_seed(elem, preceding))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let size: usize = seeds.iter().map(SeedSpec::tlv_size).sum();
+    if size > MAX_SEED_CONFIG_LEN {
+        return Err(syn::Error::new_spanned(
+            expr,
+            format!(
+                "seed configuration takes {} bytes, but at most {} fit in an extra account meta",
+                size, MAX_SEED_CONFIG_LEN
+            ),
+        ));
+    }
+
+    Ok(seeds)
+}
+
+fn parse_seed(expr: &Expr, preceding: &[Preceding]) -> syn::Result<SeedSpec> {
+    if let Expr::Path(path) = expr {
+        if path.path.is_ident("amount") {
+            return Ok(SeedSpec::InstructionData {
+                index: 8,
+                length: 8,
+            });
+        }
+    }
+
+    let call = match expr {
+        Expr::Call(call) => call,
+        _ => return Err(unknown_seed(expr)),
+    };
+    let name = match &*call.func {
+        Expr::Path(path) => path.path.get_ident().map(|ident| ident.to_string()),
+        _ => None,
+    };
+
+    match name.as_deref() {
+        Some("literal") => {
+            let [value] = args::<1>(call, "literal(bytes)")?;
+            let bytes = literal_bytes(value)?;
+            if bytes.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    value,
+                    "literal seed cannot be empty",
+                ));
+            }
+            Ok(SeedSpec::Literal { bytes })
+        }
+        Some("ix_data") => {
+            let [index, length] = args::<2>(call, "ix_data(offset, len)")?;
+            let (index, length) = (lit_u8(index)?, lit_u8(length)?);
+            check_length(length, call)?;
+            if index as usize + length as usize > EXECUTE_DATA_LEN {
+                return Err(syn::Error::new_spanned(
+                    call,
+                    format!(
+                        "instruction data range {}..{} is out of bounds, the Execute instruction data is {} bytes",
+                        index,
+                        index as usize + length as usize,
+                        EXECUTE_DATA_LEN
+                    ),
+                ));
+            }
+            Ok(SeedSpec::InstructionData { index, length })
+        }
+        Some("account_key") => {
+            let [account] = args::<1>(call, "account_key(account)")?;
+            let index = account_index(account, preceding)?;
+            Ok(SeedSpec::AccountKey { index })
+        }
+        Some("account_data") => {
+            let [account, offset, length] = args::<3>(call, "account_data(account, offset, len)")?;
+            let account_index = account_index(account, preceding)?;
+            let (data_index, length) = (lit_u8(offset)?, lit_u8(length)?);
+            check_length(length, call)?;
+            Ok(SeedSpec::AccountData {
+                account_index,
+                data_index,
+                length,
+            })
+        }
+        Some(field @ ("mint_of" | "owner_of" | "delegate_of")) => {
+            let usage = format!("{}(account)", field);
+            let [account] = args::<1>(call, &usage)?;
+            let account_index = account_index(account, preceding)?;
+            let data_index = match field {
+                "mint_of" => TOKEN_ACCOUNT_MINT_OFFSET,
+                "owner_of" => TOKEN_ACCOUNT_OWNER_OFFSET,
+                _ => TOKEN_ACCOUNT_DELEGATE_OFFSET,
+            };
+            Ok(SeedSpec::AccountData {
+                account_index,
+                data_index,
+                length: 32,
+            })
+        }
+        _ => Err(unknown_seed(expr)),
+    }
+}
+
+fn unknown_seed(expr: &Expr) -> syn::Error {
+    syn::Error::new_spanned(
+        expr,
+        "unknown seed, expected one of `literal(bytes)`, `ix_data(offset, len)`, `amount`, \
+         `account_key(account)`, `account_data(account, offset, len)`, `mint_of(account)`, \
+         `owner_of(account)` or `delegate_of(account)`",
+    )
+}
+
+fn args<'a, const N: usize>(call: &'a ExprCall, usage: &str) -> syn::Result<[&'a Expr; N]> {
+    let args = call.args.iter().collect::<Vec<_>>();
+    args.try_into()
+        .map_err(|_| syn::Error::new_spanned(call, format!("expected `{}`", usage)))
+}
+
+fn check_length(length: u8, call: &ExprCall) -> syn::Result<()> {
+    if length == 0 || length as usize > MAX_SEED_CONFIG_LEN {
+        return Err(syn::Error::new_spanned(
+            call,
+            format!("seed length must be between 1 and {}", MAX_SEED_CONFIG_LEN),
+        ));
+    }
+    Ok(())
+}
+
+fn literal_bytes(expr: &Expr) -> syn::Result<Vec<u8>> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::ByteStr(bytes) => Ok(bytes.value()),
+            Lit::Str(string) => Ok(string.value().into_bytes()),
+            _ => Err(syn::Error::new_spanned(
+                expr,
+                "expected a byte string or string literal",
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "expected a byte string or string literal",
+        )),
+    }
+}
+
+fn lit_u8(expr: &Expr) -> syn::Result<u8> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => int.base10_parse::<u8>(),
+            _ => Err(syn::Error::new_spanned(expr, "expected an integer literal")),
+        },
+        _ => Err(syn::Error::new_spanned(expr, "expected an integer literal")),
+    }
+}
+
+/// Resolves an account reference to an expression for its index in the
+/// `Execute` accounts.
+///
+/// The reference is either a raw index, the name of a base account or the name
+/// of one of the `preceding` extra metas. Fields are indexed relative to the
+/// struct's first meta, so the result stays correct when the struct is
+/// flattened into another one. Raw indices are kept as they are.
+pub fn account_index(expr: &Expr, preceding: &[Preceding]) -> syn::Result<TokenStream> {
+    let index = match expr {
+        Expr::Path(path) => {
+            let name = path
+                .path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+            if let Some(index) = BASE_ACCOUNT_NAMES.iter().position(|base| *base == name) {
+                index
+            } else if let Some(field) = preceding.iter().find(|field| field.name == name) {
+                return field.index.clone().ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        expr,
+                        format!("`{}` is a flattened struct, not an account", name),
+                    )
+                });
+            } else {
+                return Err(syn::Error::new_spanned(
+                    expr,
+                    format!(
+                        "unknown account `{}`, expected an account index, one of {} \
+                         or an extra meta declared before this one",
+                        name,
+                        BASE_ACCOUNT_NAMES.join(", ")
+                    ),
+                ));
+            }
+        }
+        _ => lit_u8(expr)? as usize,
+    };
+
+    if index < BASE_ACCOUNTS {
+        let index = index as u8;
+        return Ok(quote! { #index });
+    }
+
+    if preceding.iter().any(|field| field.index.is_none()) {
+        return Err(syn::Error::new_spanned(
+            expr,
+            "extra metas cannot be referred to by index after a #[meta(flatten)] field, \
+             use the name of the field instead",
+        ));
+    }
+    let resolved = BASE_ACCOUNTS + preceding.len();
+    if index >= resolved {
+        return Err(syn::Error::new_spanned(
+            expr,
+            format!(
+                "account index {} is not resolved before this meta, only indices below {} can be used",
+                index, resolved
+            ),
+        ));
+    }
+    // Raw indices are absolute, which is why a struct using them cannot be flattened
+    let index = index as u8;
+    Ok(quote! { #index })
+}
+
+/// Whether the account reference `expr` is the raw index of an extra meta
+/// rather than a base account or a field name.
+pub fn is_raw_meta_index(expr: &Expr) -> bool {
+    matches!(lit_u8(expr), Ok(index) if index as usize >= BASE_ACCOUNTS)
+}
+
+/// Whether any seed in `seeds = [...]` refers to an extra meta by raw index.
+pub fn uses_raw_meta_index(seeds: &Expr) -> bool {
+    let Expr::Array(array) = seeds else {
+        return false;
+    };
+    array.elems.iter().any(|seed| match seed {
+        Expr::Call(call) => {
+            let refers_to_account = matches!(
+                &*call.func,
+                Expr::Path(path) if path.path.get_ident().is_some_and(|name| {
+                    ["account_key", "account_data", "mint_of", "owner_of", "delegate_of"]
+                        .iter()
+                        .any(|seed| name == seed)
+                })
+            );
+            refers_to_account && call.args.first().is_some_and(is_raw_meta_index)
+        }
+        _ => false,
+    })
+}
@@ -0,0 +1,324 @@
+use std::{
+    cell::{Ref, RefMut},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use solana_program::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    state::{Account, Mint},
+};
+
+pub use kaptn_derive_accounts::Accounts;
+
+use crate::state::{State, ZeroCopy};
+
+/// The accounts of a custom instruction, implemented by `#[derive(Accounts)]`.
+pub trait Accounts<'info>: Sized {
+    /// Loads the struct from the front of `accounts`, leaving the accounts
+    /// after it in the slice.
+    fn try_accounts(accounts: &mut &[AccountInfo<'info>]) -> Result<Self, ProgramError>;
+}
+
+impl<'info> Accounts<'info> for () {
+    fn try_accounts(_accounts: &mut &[AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        Ok(())
+    }
+}
+
+/// Types that can be built from an extra meta's `AccountInfo`.
+///
+/// Every field of an `ExtraMetas` struct is loaded through this trait, so the
+/// wrappers below validate their account while the struct is being built.
+pub trait FromAccountInfo<'info>: Sized {
+    fn from_account_info(info: &AccountInfo<'info>) -> Result<Self, ProgramError>;
+}
+
+impl<'info> FromAccountInfo<'info> for AccountInfo<'info> {
+    fn from_account_info(info: &AccountInfo<'info>) -> Result<Self, ProgramError> {
+        Ok(info.clone())
+    }
+}
+
+/// A Token-2022 token account, unpacked and checked to be initialized.
+#[derive(Clone)]
+pub struct TokenAccount<'info> {
+    info: AccountInfo<'info>,
+    account: Account,
+}
+
+impl<'info> TokenAccount<'info> {
+    pub fn key(&self) -> &'info Pubkey {
+        self.info.key
+    }
+
+    pub fn to_account_info(&self) -> AccountInfo<'info> {
+        self.info.clone()
+    }
+}
+
+impl<'info> AsRef<AccountInfo<'info>> for TokenAccount<'info> {
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        &self.info
+    }
+}
+
+impl<'info> FromAccountInfo<'info> for TokenAccount<'info> {
+    fn from_account_info(info: &AccountInfo<'info>) -> Result<Self, ProgramError> {
+        check_token_program_owner(info)?;
+        let data = info.try_borrow_data()?;
+        let account = StateWithExtensions::<Account>::unpack(&data)?.base;
+        Ok(Self {
+            info: info.clone(),
+            account,
+        })
+    }
+}
+
+impl Deref for TokenAccount<'_> {
+    type Target = Account;
+
+    fn deref(&self) -> &Self::Target {
+        &self.account
+    }
+}
+
+/// A Token-2022 mint, unpacked and checked to be initialized.
+#[derive(Clone)]
+pub struct MintAccount<'info> {
+    info: AccountInfo<'info>,
+    mint: Mint,
+}
+
+impl<'info> MintAccount<'info> {
+    pub fn key(&self) -> &'info Pubkey {
+        self.info.key
+    }
+
+    pub fn to_account_info(&self) -> AccountInfo<'info> {
+        self.info.clone()
+    }
+}
+
+impl<'info> AsRef<AccountInfo<'info>> for MintAccount<'info> {
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        &self.info
+    }
+}
+
+impl<'info> FromAccountInfo<'info> for MintAccount<'info> {
+    fn from_account_info(info: &AccountInfo<'info>) -> Result<Self, ProgramError> {
+        check_token_program_owner(info)?;
+        let data = info.try_borrow_data()?;
+        let mint = StateWithExtensions::<Mint>::unpack(&data)?.base;
+        Ok(Self {
+            info: info.clone(),
+            mint,
+        })
+    }
+}
+
+impl Deref for MintAccount<'_> {
+    type Target = Mint;
+
+    fn deref(&self) -> &Self::Target {
+        &self.mint
+    }
+}
+
+/// An executable program account.
+#[derive(Clone)]
+pub struct Program<'info> {
+    info: AccountInfo<'info>,
+}
+
+impl<'info> Program<'info> {
+    pub fn key(&self) -> &'info Pubkey {
+        self.info.key
+    }
+
+    pub fn to_account_info(&self) -> AccountInfo<'info> {
+        self.info.clone()
+    }
+}
+
+impl<'info> AsRef<AccountInfo<'info>> for Program<'info> {
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        &self.info
+    }
+}
+
+impl<'info> FromAccountInfo<'info> for Program<'info> {
+    fn from_account_info(info: &AccountInfo<'info>) -> Result<Self, ProgramError> {
+        if !info.executable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self { info: info.clone() })
+    }
+}
+
+/// A sysvar account, checked against the sysvar's id and deserialized.
+///
+/// Named `SysvarAccount` so it does not clash with the `Sysvar` trait.
+#[derive(Clone)]
+pub struct SysvarAccount<'info, T: Sysvar> {
+    info: AccountInfo<'info>,
+    sysvar: T,
+}
+
+impl<'info, T: Sysvar> SysvarAccount<'info, T> {
+    pub fn key(&self) -> &'info Pubkey {
+        self.info.key
+    }
+
+    pub fn to_account_info(&self) -> AccountInfo<'info> {
+        self.info.clone()
+    }
+}
+
+impl<'info, T: Sysvar> AsRef<AccountInfo<'info>> for SysvarAccount<'info, T> {
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        &self.info
+    }
+}
+
+impl<'info, T: Sysvar> FromAccountInfo<'info> for SysvarAccount<'info, T> {
+    fn from_account_info(info: &AccountInfo<'info>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            info: info.clone(),
+            sysvar: T::from_account_info(info)?,
+        })
+    }
+}
+
+impl<T: Sysvar> Deref for SysvarAccount<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.sysvar
+    }
+}
+
+/// An account of the hook program holding a `T`, checked for the owner and
+/// discriminator of `T` and deserialized.
+///
+/// Changes made through `DerefMut` are written back to the account by `save`.
+#[derive(Clone)]
+pub struct StateAccount<'info, T: State> {
+    info: AccountInfo<'info>,
+    state: T,
+}
+
+impl<'info, T: State> StateAccount<'info, T> {
+    pub fn key(&self) -> &'info Pubkey {
+        self.info.key
+    }
+
+    pub fn to_account_info(&self) -> AccountInfo<'info> {
+        self.info.clone()
+    }
+
+    /// Serializes the state back into the account's data.
+    pub fn save(&self) -> Result<(), ProgramError> {
+        let mut data = self.info.try_borrow_mut_data()?;
+        self.state.try_to_data(&mut data)
+    }
+}
+
+impl<'info, T: State> AsRef<AccountInfo<'info>> for StateAccount<'info, T> {
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        &self.info
+    }
+}
+
+impl<'info, T: State> FromAccountInfo<'info> for StateAccount<'info, T> {
+    fn from_account_info(info: &AccountInfo<'info>) -> Result<Self, ProgramError> {
+        if *info.owner != T::OWNER {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let data = info.try_borrow_data()?;
+        let state = T::try_from_data(&data)?;
+        Ok(Self {
+            info: info.clone(),
+            state,
+        })
+    }
+}
+
+impl<T: State> Deref for StateAccount<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+impl<T: State> DerefMut for StateAccount<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.state
+    }
+}
+
+/// An account of the hook program holding a zero-copy `T`, checked for the
+/// owner, discriminator, size and alignment of `T`.
+///
+/// The data is not decoded, `load` and `load_mut` borrow it in place.
+#[derive(Clone)]
+pub struct ZeroCopyAccount<'info, T: ZeroCopy> {
+    info: AccountInfo<'info>,
+    state: PhantomData<T>,
+}
+
+impl<'info, T: ZeroCopy> ZeroCopyAccount<'info, T> {
+    pub fn key(&self) -> &'info Pubkey {
+        self.info.key
+    }
+
+    pub fn to_account_info(&self) -> AccountInfo<'info> {
+        self.info.clone()
+    }
+
+    /// Borrows the account's data as a `T`.
+    pub fn load(&self) -> Result<Ref<'_, T>, ProgramError> {
+        let data = self.info.try_borrow_data()?;
+        Ref::filter_map(data, |data| T::from_data(data).ok())
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Mutably borrows the account's data as a `T`, changes are written to the
+    /// account directly.
+    pub fn load_mut(&self) -> Result<RefMut<'_, T>, ProgramError> {
+        let data = self.info.try_borrow_mut_data()?;
+        RefMut::filter_map(data, |data| T::from_data_mut(data).ok())
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl<'info, T: ZeroCopy> AsRef<AccountInfo<'info>> for ZeroCopyAccount<'info, T> {
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        &self.info
+    }
+}
+
+impl<'info, T: ZeroCopy> FromAccountInfo<'info> for ZeroCopyAccount<'info, T> {
+    fn from_account_info(info: &AccountInfo<'info>) -> Result<Self, ProgramError> {
+        if *info.owner != T::OWNER {
+            return Err(ProgramError::IllegalOwner);
+        }
+        T::from_data(&info.try_borrow_data()?)?;
+        Ok(Self {
+            info: info.clone(),
+            state: PhantomData,
+        })
+    }
+}
+
+pub(crate) fn check_token_program_owner(info: &AccountInfo) -> Result<(), ProgramError> {
+    if *info.owner != spl_token_2022::id() {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
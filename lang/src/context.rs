@@ -1,6 +1,33 @@
+use core::cell::{OnceCell, RefCell};
+
 pub use kaptn_derive_extrametas::ExtraMetas;
-use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
-use spl_tlv_account_resolution::account::ExtraAccountMeta;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::{Clock, Epoch},
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, error::AccountResolutionError, seeds::Seed,
+};
+use spl_token_2022::{
+    amount_to_ui_amount,
+    extension::{
+        permanent_delegate::PermanentDelegate, transfer_fee::TransferFeeConfig, BaseState,
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::{Account, Mint},
+};
+use spl_transfer_hook_interface::instruction::TransferHookInstruction;
+
+use crate::{accounts::check_token_program_owner, error::KaptnError, metadata::MetaField};
 
 pub struct TransferContext<'a, 'info, E = ()> {
     pub program_id: &'a Pubkey,
@@ -11,19 +38,570 @@ pub struct TransferContext<'a, 'info, E = ()> {
     pub extra_account_metas: &'a AccountInfo<'info>,
     pub amount: u64,
     pub extra_metas: E,
+    /// Accounts passed after the last extra meta in the validation account.
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+    accounts: &'a [AccountInfo<'info>],
+    /// Seeds and bump of the PDA extra metas signed for so far, by meta index.
+    signer_seeds: RefCell<Vec<(usize, SignerSeeds)>>,
+    source: OnceCell<Account>,
+    destination: OnceCell<Account>,
+    mint_state: OnceCell<Mint>,
+    transfer_fee_config: OnceCell<Option<TransferFeeConfig>>,
+    permanent_delegate: OnceCell<Option<Pubkey>>,
+}
+
+/// What allowed the `authority` of a transfer to move the tokens.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AuthorityKind {
+    /// The owner of the source token account.
+    Owner,
+    /// The delegate approved on the source token account.
+    Delegate,
+    /// The mint's `PermanentDelegate`, e.g. an issuer clawback.
+    PermanentDelegate,
+    /// None of the above. Token-2022 clears the delegate of the source account
+    /// once its allowance is spent, before the hook runs, so this is also what
+    /// a delegate that transferred its whole allowance looks like.
+    Unknown,
+}
+
+impl<'a, 'info, E> TransferContext<'a, 'info, E> {
+    /// Builds the context from the `Execute` accounts, the base accounts are
+    /// the first five of `accounts`.
+    pub fn new(
+        program_id: &'a Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+        amount: u64,
+        extra_metas: E,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let [source_account, mint, destination_account, authority, extra_account_metas, ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        Ok(Self {
+            program_id,
+            source_account,
+            mint,
+            destination_account,
+            authority,
+            extra_account_metas,
+            amount,
+            extra_metas,
+            remaining_accounts,
+            accounts,
+            signer_seeds: RefCell::new(vec![]),
+            source: OnceCell::new(),
+            destination: OnceCell::new(),
+            mint_state: OnceCell::new(),
+            transfer_fee_config: OnceCell::new(),
+            permanent_delegate: OnceCell::new(),
+        })
+    }
+
+    /// Builds the context from the resolver that built `extra_metas`, keeping
+    /// the seeds it found for the PDA extra metas of the hook program.
+    pub fn from_resolver(
+        resolver: MetaResolver<'a, 'info>,
+        extra_metas: E,
+    ) -> Result<Self, ProgramError> {
+        let remaining_accounts = resolver.remaining_accounts();
+        let ctx = Self::new(
+            resolver.program_id,
+            resolver.accounts,
+            resolver.amount,
+            extra_metas,
+            remaining_accounts,
+        )?;
+        ctx.signer_seeds.replace(resolver.signer_seeds);
+        Ok(ctx)
+    }
+
+    /// The source token account. The hook runs after the tokens are moved, so
+    /// its balance no longer includes the transferred amount.
+    ///
+    /// Decoded on first use and cached for the rest of the handler.
+    pub fn source_token_account(&self) -> Result<&Account, ProgramError> {
+        cached(&self.source, || unpack_token_state(self.source_account))
+    }
+
+    /// The destination token account, which already holds the transferred amount.
+    pub fn destination_token_account(&self) -> Result<&Account, ProgramError> {
+        cached(&self.destination, || {
+            unpack_token_state(self.destination_account)
+        })
+    }
+
+    /// The mint being transferred.
+    pub fn mint_account(&self) -> Result<&Mint, ProgramError> {
+        cached(&self.mint_state, || unpack_token_state(self.mint))
+    }
+
+    /// The wallet that owns the source token account.
+    pub fn source_owner(&self) -> Result<Pubkey, ProgramError> {
+        Ok(self.source_token_account()?.owner)
+    }
+
+    /// The wallet that owns the destination token account.
+    pub fn destination_owner(&self) -> Result<Pubkey, ProgramError> {
+        Ok(self.destination_token_account()?.owner)
+    }
+
+    /// Balance of the source token account after the transfer.
+    pub fn source_balance(&self) -> Result<u64, ProgramError> {
+        Ok(self.source_token_account()?.amount)
+    }
+
+    /// Balance of the destination token account after the transfer.
+    pub fn destination_balance(&self) -> Result<u64, ProgramError> {
+        Ok(self.destination_token_account()?.amount)
+    }
+
+    /// The delegate of the source token account, if it has one.
+    pub fn source_delegate(&self) -> Result<Option<Pubkey>, ProgramError> {
+        Ok(self.source_token_account()?.delegate.into())
+    }
+
+    pub fn decimals(&self) -> Result<u8, ProgramError> {
+        Ok(self.mint_account()?.decimals)
+    }
+
+    pub fn supply(&self) -> Result<u64, ProgramError> {
+        Ok(self.mint_account()?.supply)
+    }
+
+    /// The transferred amount in whole tokens, using the mint's decimals.
+    pub fn ui_amount(&self) -> Result<f64, ProgramError> {
+        Ok(amount_to_ui_amount(self.amount, self.decimals()?))
+    }
+
+    /// The mint's `TransferFeeConfig` extension, if it has one.
+    pub fn transfer_fee_config(&self) -> Result<Option<&TransferFeeConfig>, ProgramError> {
+        let config = cached(&self.transfer_fee_config, || {
+            check_token_program_owner(self.mint)?;
+            let data = self.mint.try_borrow_data()?;
+            let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+            Ok(mint.get_extension::<TransferFeeConfig>().ok().copied())
+        })?;
+        Ok(config.as_ref())
+    }
+
+    /// The fee withheld from `amount` in the current epoch, zero if the mint
+    /// has no transfer fee.
+    ///
+    /// `amount` is the gross amount taken from the source, the destination
+    /// receives `net_amount`.
+    pub fn transfer_fee(&self) -> Result<u64, ProgramError> {
+        self.transfer_fee_at_epoch(Clock::get()?.epoch)
+    }
+
+    /// The amount the destination receives in the current epoch, after the fee.
+    pub fn net_amount(&self) -> Result<u64, ProgramError> {
+        self.net_amount_at_epoch(Clock::get()?.epoch)
+    }
+
+    /// The fee withheld from `amount` in `epoch`, using the same math as Token-2022.
+    pub fn transfer_fee_at_epoch(&self, epoch: Epoch) -> Result<u64, ProgramError> {
+        match self.transfer_fee_config()? {
+            Some(config) => config
+                .calculate_epoch_fee(epoch, self.amount)
+                .ok_or(ProgramError::ArithmeticOverflow),
+            None => Ok(0),
+        }
+    }
+
+    /// The amount the destination receives in `epoch`, after the fee.
+    pub fn net_amount_at_epoch(&self, epoch: Epoch) -> Result<u64, ProgramError> {
+        self.amount
+            .checked_sub(self.transfer_fee_at_epoch(epoch)?)
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    /// The mint's permanent delegate, if it has the `PermanentDelegate` extension
+    /// with a delegate set.
+    pub fn permanent_delegate(&self) -> Result<Option<Pubkey>, ProgramError> {
+        let delegate = cached(&self.permanent_delegate, || {
+            check_token_program_owner(self.mint)?;
+            let data = self.mint.try_borrow_data()?;
+            let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+            Ok(mint
+                .get_extension::<PermanentDelegate>()
+                .ok()
+                .and_then(|extension| Option::<Pubkey>::from(extension.delegate)))
+        })?;
+        Ok(*delegate)
+    }
+
+    /// Classifies the `authority` against the mint's permanent delegate and the
+    /// source account's delegate and owner, in the order Token-2022 checks them.
+    pub fn authority_kind(&self) -> Result<AuthorityKind, ProgramError> {
+        let authority = self.authority.key;
+        if self.permanent_delegate()?.as_ref() == Some(authority) {
+            return Ok(AuthorityKind::PermanentDelegate);
+        }
+        let source = self.source_token_account()?;
+        if source.delegate.as_ref() == COption::Some(authority) {
+            Ok(AuthorityKind::Delegate)
+        } else if source.owner == *authority {
+            Ok(AuthorityKind::Owner)
+        } else {
+            Ok(AuthorityKind::Unknown)
+        }
+    }
+}
+
+impl<'a, 'info, E: ExtraMetas<'info>> TransferContext<'a, 'info, E> {
+    /// The seeds and bump of the extra meta at `index` in the validation
+    /// account, for signing as that PDA of the hook program.
+    ///
+    /// `#[derive(ExtraMetas)]` generates a typed accessor calling this for
+    /// each such field, e.g. `MyExtraMetas::vault_signer_seeds(&ctx)`. The
+    /// seeds found while resolving the extra metas are reused, so the bump is
+    /// only searched for once per transfer.
+    pub fn signer_seeds(&self, index: usize) -> Result<SignerSeeds, ProgramError> {
+        let cached = self
+            .signer_seeds
+            .borrow()
+            .iter()
+            .find(|(cached, _)| *cached == index)
+            .map(|(_, seeds)| seeds.clone());
+        if let Some(seeds) = cached {
+            return Ok(seeds);
+        }
+
+        let metas = E::to_extra_account_metas();
+        let meta = index
+            .checked_sub(5)
+            .and_then(|meta_index| metas.get(meta_index))
+            .ok_or(ProgramError::InvalidArgument)?;
+        if meta.discriminator != 1 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // Only resolved when the context was built without a resolver
+        let mut resolver = MetaResolver::new(self.program_id, self.accounts, self.amount)?;
+        for (meta_field, meta) in E::meta_fields().iter().zip(metas.iter()).take(index - 4) {
+            resolver.next_optional(&meta_field.name, meta)?;
+        }
+        let seeds = resolver
+            .signer_seeds
+            .iter()
+            .find(|(resolved, _)| *resolved == index)
+            .map(|(_, seeds)| seeds.clone())
+            .ok_or(ProgramError::from(AccountResolutionError::AccountNotFound))?;
+        self.signer_seeds.borrow_mut().push((index, seeds.clone()));
+        Ok(seeds)
+    }
+}
+
+/// Seeds of a PDA of the hook program, bump included.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignerSeeds {
+    seeds: Vec<Vec<u8>>,
+}
+
+impl SignerSeeds {
+    /// The seeds in the form `invoke_signed` takes, e.g.
+    /// `invoke_signed(&ix, &infos, &[&a.as_slices(), &b.as_slices()])`.
+    pub fn as_slices(&self) -> Vec<&[u8]> {
+        self.seeds.iter().map(Vec::as_slice).collect()
+    }
+
+    pub fn bump(&self) -> u8 {
+        self.seeds.last().map_or(0, |bump| bump[0])
+    }
+
+    /// Invokes `instruction` signed by the PDA of these seeds.
+    pub fn invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+    ) -> ProgramResult {
+        invoke_signed(instruction, account_infos, &[&self.as_slices()])
+    }
+}
+
+/// Returns the value in `cell`, initializing it with `init` on first use.
+fn cached<T>(
+    cell: &OnceCell<T>,
+    init: impl FnOnce() -> Result<T, ProgramError>,
+) -> Result<&T, ProgramError> {
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+    let value = init()?;
+    Ok(cell.get_or_init(|| value))
+}
+
+/// Unpacks the base state of a Token-2022 account, failing with `IllegalOwner`
+/// if it is not owned by Token-2022.
+fn unpack_token_state<S: BaseState + Pack>(info: &AccountInfo) -> Result<S, ProgramError> {
+    check_token_program_owner(info)?;
+    let data = info.try_borrow_data()?;
+    Ok(StateWithExtensions::<S>::unpack(&data)?.base)
 }
 
 pub trait ExtraMetas<'info>: Sized {
-    fn from_accounts(accounts: &[AccountInfo<'info>]) -> Result<Self, ProgramError>;
-    fn to_extra_account_metas() -> Vec<ExtraAccountMeta>;
+    /// Number of extra metas the struct adds to the validation account.
+    const LEN: usize;
+
+    /// Whether the struct can be embedded with `#[meta(flatten)]`. Structs whose
+    /// seeds refer to their own metas by raw index cannot, as those indices are
+    /// absolute.
+    const FLATTENABLE: bool = true;
+
+    /// Builds the struct from the `Execute` accounts, binding every field to
+    /// the account at its resolved address.
+    fn from_accounts(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+        amount: u64,
+    ) -> Result<Self, ProgramError> {
+        let mut resolver = MetaResolver::new(program_id, accounts, amount)?;
+        Self::from_resolver(&mut resolver)
+    }
+
+    /// Builds the struct from the next extra metas of `resolver`.
+    fn from_resolver(resolver: &mut MetaResolver<'_, 'info>) -> Result<Self, ProgramError>;
+
+    fn to_extra_account_metas() -> Vec<ExtraAccountMeta> {
+        Self::to_extra_account_metas_at(5)
+    }
+
+    /// The extra metas of the struct when its first meta is the account at
+    /// `offset`, so seeds referring to its own fields point at the right index.
+    fn to_extra_account_metas_at(offset: u8) -> Vec<ExtraAccountMeta>;
+
+    /// Names of the extra metas, in the order of `to_extra_account_metas`.
+    fn meta_fields() -> Vec<MetaField>;
+
+    /// Describes the extra metas for off-chain tooling.
+    #[cfg(not(target_os = "solana"))]
+    fn describe() -> Vec<crate::metadata::ExtraMetaDescription> {
+        crate::metadata::describe::<Self>()
+    }
+
+    /// The description of the extra metas as JSON, see `describe`.
+    #[cfg(not(target_os = "solana"))]
+    fn to_json() -> String {
+        crate::metadata::to_json::<Self>()
+    }
 }
 
 impl<'info> ExtraMetas<'info> for () {
-    fn from_accounts(_accounts: &[AccountInfo<'info>]) -> Result<Self, ProgramError> {
+    const LEN: usize = 0;
+
+    fn from_resolver(_resolver: &mut MetaResolver<'_, 'info>) -> Result<Self, ProgramError> {
         Ok(())
     }
 
-    fn to_extra_account_metas() -> Vec<ExtraAccountMeta> {
+    fn to_extra_account_metas_at(_offset: u8) -> Vec<ExtraAccountMeta> {
         vec![]
     }
+
+    fn meta_fields() -> Vec<MetaField> {
+        vec![]
+    }
+}
+
+/// Resolves extra metas one by one and finds the account passed for each.
+///
+/// Accounts are looked up by their resolved address rather than by position,
+/// so the extra metas may be passed in any order. The resolved accounts are
+/// kept in the order of the validation account, which is what the account
+/// indices in seeds refer to.
+pub struct MetaResolver<'a, 'info> {
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+    amount: u64,
+    instruction_data: Vec<u8>,
+    resolved: Vec<Option<&'a AccountInfo<'info>>>,
+    /// Seeds and bump of the PDA extra metas of the hook program, by meta index.
+    signer_seeds: Vec<(usize, SignerSeeds)>,
+}
+
+impl<'a, 'info> MetaResolver<'a, 'info> {
+    pub fn new(
+        program_id: &'a Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+        amount: u64,
+    ) -> Result<Self, ProgramError> {
+        let base = accounts
+            .get(..5)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        Ok(Self {
+            program_id,
+            accounts,
+            amount,
+            instruction_data: TransferHookInstruction::Execute { amount }.pack(),
+            resolved: base.iter().map(Some).collect(),
+            signer_seeds: vec![],
+        })
+    }
+
+    /// All accounts passed to the instruction.
+    pub fn accounts(&self) -> &'a [AccountInfo<'info>] {
+        self.accounts
+    }
+
+    /// Index of the next extra meta in the validation account.
+    pub fn position(&self) -> usize {
+        self.resolved.len()
+    }
+
+    /// Resolves the required extra meta `name` and returns the account passed for it.
+    pub fn next(
+        &mut self,
+        name: &str,
+        meta: &ExtraAccountMeta,
+    ) -> Result<&'a AccountInfo<'info>, ProgramError> {
+        let position = self.position();
+        let address = self.resolve(meta).map_err(|err| {
+            msg!("Failed to resolve extra meta `{}`: {}", name, err);
+            err
+        })?;
+        match self.find(&address) {
+            Some(info) => self.bind(name, meta, info),
+            None => match self.accounts.get(position) {
+                Some(found) => {
+                    msg!(
+                        "Extra meta `{}` should be {}, found {}",
+                        name,
+                        address,
+                        found.key
+                    );
+                    Err(AccountResolutionError::IncorrectAccount.into())
+                }
+                None => {
+                    msg!("Extra meta `{}` ({}) was not passed", name, address);
+                    Err(ProgramError::NotEnoughAccountKeys)
+                }
+            },
+        }
+    }
+
+    /// Resolves the optional extra meta `name`, returning `None` if it was not
+    /// passed or depends on an optional meta that was not passed.
+    pub fn next_optional(
+        &mut self,
+        name: &str,
+        meta: &ExtraAccountMeta,
+    ) -> Result<Option<&'a AccountInfo<'info>>, ProgramError> {
+        let found = self
+            .resolve(meta)
+            .ok()
+            .and_then(|address| self.find(&address));
+        match found {
+            Some(info) => self.bind(name, meta, info).map(Some),
+            None => {
+                self.resolved.push(None);
+                Ok(None)
+            }
+        }
+    }
+
+    /// The accounts after the extra metas bound so far, which are left for
+    /// the handler. Optional metas that were not passed take no account.
+    pub fn remaining_accounts(&self) -> &'a [AccountInfo<'info>] {
+        let bound = self.resolved.iter().flatten().count();
+        self.accounts.get(bound..).unwrap_or(&[])
+    }
+
+    /// Binds `info` to the extra meta `name`, checking it has the privileges
+    /// the meta declares.
+    fn bind(
+        &mut self,
+        name: &str,
+        meta: &ExtraAccountMeta,
+        info: &'a AccountInfo<'info>,
+    ) -> Result<&'a AccountInfo<'info>, ProgramError> {
+        if bool::from(meta.is_signer) && !info.is_signer {
+            msg!("Extra meta `{}` must be a signer", name);
+            return Err(KaptnError::ConstraintSigner.into());
+        }
+        if bool::from(meta.is_writable) && !info.is_writable {
+            msg!("Extra meta `{}` must be writable", name);
+            return Err(KaptnError::ConstraintWritable.into());
+        }
+        self.resolved.push(Some(info));
+        Ok(info)
+    }
+
+    /// The address of `meta`. PDAs of the hook program are derived here rather
+    /// than by `ExtraAccountMeta::resolve`, to keep their seeds and bump.
+    fn resolve(&mut self, meta: &ExtraAccountMeta) -> Result<Pubkey, ProgramError> {
+        if meta.discriminator == 1 {
+            let seeds = Seed::unpack_address_config(&meta.address_config)?;
+            let mut seeds = self.seed_bytes(&seeds)?;
+            let seed_slices = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            let (address, bump) = Pubkey::find_program_address(&seed_slices, self.program_id);
+            seeds.push(vec![bump]);
+            self.signer_seeds
+                .push((self.position(), SignerSeeds { seeds }));
+            return Ok(address);
+        }
+        let data = self
+            .resolved
+            .iter()
+            .map(|info| info.map(|info| info.try_borrow_data()).transpose())
+            .collect::<Result<Vec<_>, _>>()?;
+        let meta = meta.resolve(&self.instruction_data, self.program_id, |index| {
+            let info = self.resolved.get(index).copied().flatten()?;
+            let data = data.get(index)?.as_ref()?;
+            Some((info.key, Some(data.as_ref())))
+        })?;
+        Ok(meta.pubkey)
+    }
+
+    /// The bytes of `seeds` for the accounts resolved so far, the same way
+    /// `ExtraAccountMeta::resolve` derives a PDA.
+    fn seed_bytes(&self, seeds: &[Seed]) -> Result<Vec<Vec<u8>>, ProgramError> {
+        let account = |index: u8| {
+            self.resolved
+                .get(index as usize)
+                .copied()
+                .flatten()
+                .ok_or(ProgramError::from(AccountResolutionError::AccountNotFound))
+        };
+        let slice = |bytes: &[u8], offset: u8, length: u8, err: AccountResolutionError| {
+            bytes
+                .get(offset as usize..offset as usize + length as usize)
+                .map(<[u8]>::to_vec)
+                .ok_or(ProgramError::from(err))
+        };
+        seeds
+            .iter()
+            .filter(|seed| !matches!(seed, Seed::Uninitialized))
+            .map(|seed| match seed {
+                Seed::Literal { bytes } => Ok(bytes.clone()),
+                Seed::InstructionData { index, length } => slice(
+                    &self.instruction_data,
+                    *index,
+                    *length,
+                    AccountResolutionError::InstructionDataTooSmall,
+                ),
+                Seed::AccountKey { index } => Ok(account(*index)?.key.to_bytes().to_vec()),
+                Seed::AccountData {
+                    account_index,
+                    data_index,
+                    length,
+                } => slice(
+                    &account(*account_index)?.try_borrow_data()?,
+                    *data_index,
+                    *length,
+                    AccountResolutionError::AccountDataTooSmall,
+                ),
+                Seed::Uninitialized => unreachable!("uninitialized seeds are filtered out"),
+            })
+            .collect()
+    }
+
+    /// Finds the account with the given address, checking the next position first.
+    fn find(&self, address: &Pubkey) -> Option<&'a AccountInfo<'info>> {
+        self.accounts
+            .get(self.position())
+            .filter(|info| info.key == address)
+            .or_else(|| self.accounts.iter().find(|info| info.key == address))
+    }
 }
@@ -0,0 +1,33 @@
+use solana_program::program_error::ProgramError;
+
+/// Errors returned by the checks kaptn generates for your program.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum KaptnError {
+    /// An extra meta is not owned by the program given in `owner = ...`
+    ConstraintOwner = 2000,
+    /// An extra meta does not have the address given in `address = ...`
+    ConstraintAddress,
+    /// An extra meta's data does not match the account given in `has_one = ...`
+    ConstraintHasOne,
+    /// A `constraint = ...` expression on an extra meta evaluated to false
+    ConstraintRaw,
+    /// An extra meta declared with `signer = true` did not sign the transaction
+    ConstraintSigner,
+    /// An extra meta declared with `writable = true` was not passed as writable
+    ConstraintWritable,
+    /// The mint of a transfer is not the one declared with `declare_mint!`, or
+    /// is rejected by the `mint_check` of `#[transfer_hook]`
+    InvalidMint,
+    /// The source or destination account of a transfer belongs to another mint
+    TokenAccountMintMismatch,
+    /// The validation account holds other extra metas than the `ExtraMetas`
+    /// struct of the hook, e.g. it was not updated after the struct changed
+    ExtraMetasMismatch,
+}
+
+impl From<KaptnError> for ProgramError {
+    fn from(e: KaptnError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
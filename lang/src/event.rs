@@ -0,0 +1,106 @@
+//! Structured events logged with `sol_log_data`.
+//!
+//! An `#[event]` struct is logged by `emit!` as its discriminator followed by
+//! its Borsh serialization. The runtime prints it as a `Program data: <base64>`
+//! line, which the host-side decoder turns back into the typed event.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// A struct that can be logged with `emit!`, implemented by `#[event]`.
+pub trait Event: BorshSerialize + BorshDeserialize {
+    /// First 8 bytes of `sha256("event:<StructName>")`.
+    const DISCRIMINATOR: [u8; 8];
+
+    /// The discriminator followed by the serialized event, as logged by `emit!`.
+    fn data(&self) -> Vec<u8> {
+        let mut data = Self::DISCRIMINATOR.to_vec();
+        self.serialize(&mut data)
+            .expect("serializing into a Vec does not fail");
+        data
+    }
+
+    /// Deserializes an event logged by `emit!`, `None` if `data` holds another
+    /// event or is malformed.
+    fn try_from_data(data: &[u8]) -> Option<Self> {
+        let payload = data.strip_prefix(&Self::DISCRIMINATOR)?;
+        Self::try_from_slice(payload).ok()
+    }
+}
+
+/// Logs an `#[event]` struct with `sol_log_data`.
+#[macro_export]
+macro_rules! emit {
+    ($event:expr $(,)?) => {
+        $crate::solana_program::log::sol_log_data(&[&$crate::event::Event::data(&$event)])
+    };
+}
+
+#[cfg(not(target_os = "solana"))]
+pub use host::*;
+
+#[cfg(not(target_os = "solana"))]
+mod host {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use solana_program::pubkey::Pubkey;
+
+    use super::Event;
+
+    const DATA_PREFIX: &str = "Program data: ";
+
+    /// Decodes a single `Program data:` log line into a `T` event.
+    ///
+    /// Returns `None` for other log lines and for data of other events.
+    pub fn decode_event<T: Event>(log: &str) -> Option<T> {
+        let encoded = log.strip_prefix(DATA_PREFIX)?;
+        // `sol_log_data` logs each slice as its own base64 field, `emit!` logs one
+        let data = STANDARD.decode(encoded.split(' ').next()?).ok()?;
+        T::try_from_data(&data)
+    }
+
+    /// Decodes every `T` event emitted by `program_id` in the log messages of
+    /// a transaction.
+    ///
+    /// Invocations are followed through the `invoke`, `success` and `failed`
+    /// lines, so events of CPIs to other programs are skipped even if they
+    /// share the discriminator.
+    pub fn decode_events<T: Event, S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<T> {
+        let program_id = program_id.to_string();
+        let mut stack: Vec<&str> = vec![];
+        let mut events = vec![];
+        for log in logs {
+            let log = log.as_ref();
+            if log.starts_with(DATA_PREFIX) {
+                if stack.last() == Some(&program_id.as_str()) {
+                    events.extend(decode_event(log));
+                }
+                continue;
+            }
+            let mut words = log.split(' ');
+            match (words.next(), words.next(), words.next(), words.next()) {
+                (Some("Program"), Some(program), Some("invoke"), Some(depth))
+                    if is_invoke(program, depth) =>
+                {
+                    stack.push(program)
+                }
+                (Some("Program"), Some(program), Some(result), _)
+                    if stack.last() == Some(&program)
+                        && (result == "success" || result.starts_with("failed")) =>
+                {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+
+    /// Whether `Program <program> invoke <depth>` is an invocation logged by
+    /// the runtime rather than e.g. a `Program log: invoke ...` message.
+    fn is_invoke(program: &str, depth: &str) -> bool {
+        program.parse::<Pubkey>().is_ok()
+            && depth
+                .strip_prefix('[')
+                .and_then(|depth| depth.strip_suffix(']'))
+                .is_some_and(|depth| depth.parse::<u8>().is_ok())
+    }
+}
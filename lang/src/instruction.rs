@@ -0,0 +1,34 @@
+//! Custom instructions of the hook program, dispatched next to the Transfer
+//! Hook interface.
+//!
+//! An `#[instruction]` function is called when the instruction data starts
+//! with its 8 byte discriminator, the first bytes of
+//! `sha256("instruction:<function_name>")`, followed by its Borsh-serialized
+//! arguments.
+
+use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::accounts::Accounts;
+
+pub struct InstructionContext<'a, 'info, T> {
+    pub program_id: &'a Pubkey,
+    pub accounts: T,
+    /// Accounts passed after those of `T`.
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info, T: Accounts<'info>> InstructionContext<'a, 'info, T> {
+    /// Loads `T` from the front of the instruction's accounts.
+    pub fn new(
+        program_id: &'a Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let mut remaining_accounts = accounts;
+        let accounts = T::try_accounts(&mut remaining_accounts)?;
+        Ok(Self {
+            program_id,
+            accounts,
+            remaining_accounts,
+        })
+    }
+}
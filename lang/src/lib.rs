@@ -1,18 +1,48 @@
+pub use kaptn_attribute_account::account;
+pub use kaptn_attribute_error::kaptn_error;
+pub use kaptn_attribute_event::event;
+pub use kaptn_attribute_instruction::instruction;
 pub use kaptn_attribute_transfer_hook::transfer_hook;
 pub use kaptn_derive_extrametas::ExtraMetas;
 pub use kaptn_macros::{declare_id, declare_mint};
 
+pub use borsh;
+pub use bytemuck;
+pub use num_traits;
 pub use solana_program;
 
+pub mod accounts;
 pub mod context;
+pub mod error;
+pub mod event;
+pub mod instruction;
+pub mod metadata;
+mod require;
+pub mod state;
 
 /// The prelude contains all commonly used components of the crate.
 /// All programs should include it via `use kaptn_lang::prelude::*;`.
 pub mod prelude {
 
     pub use super::{
-        context::{ExtraMetas, TransferContext},
-        declare_id, declare_mint, transfer_hook,
+        account,
+        accounts::{
+            Accounts, FromAccountInfo, MintAccount, Program, StateAccount, SysvarAccount,
+            TokenAccount, ZeroCopyAccount,
+        },
+        context::{AuthorityKind, ExtraMetas, MetaResolver, SignerSeeds, TransferContext},
+        declare_id, declare_mint, emit,
+        error::KaptnError,
+        event,
+        event::Event,
+        instruction,
+        instruction::InstructionContext,
+        kaptn_error,
+        metadata::MetaField,
+        require, require_eq, require_gt, require_gte, require_keys_eq, require_keys_neq,
+        require_lt, require_lte, require_neq,
+        state::{Space, State, ZeroCopy},
+        transfer_hook,
     };
 
     pub use solana_program::{
@@ -20,9 +50,10 @@ pub mod prelude {
         clock::Clock,
         entrypoint::ProgramResult,
         epoch_schedule::EpochSchedule,
+        instruction::{AccountMeta, Instruction},
         msg,
-        program::invoke_signed,
-        program_error::ProgramError,
+        program::{invoke, invoke_signed},
+        program_error::{PrintProgramError, ProgramError},
         pubkey::Pubkey,
         rent::Rent,
         stake_history::StakeHistory,
@@ -52,4 +83,5 @@ pub mod prelude {
         },
         onchain,
     };
+    pub use spl_type_length_value::state::TlvStateBorrowed;
 }
@@ -0,0 +1,208 @@
+//! Description of an `ExtraMetas` struct's accounts for off-chain tooling.
+//!
+//! The CLI, tests and front-ends can read the account layout of a hook
+//! straight from the crate with `MyExtraMetas::describe()` or
+//! `MyExtraMetas::to_json()`, without deploying it and reading the validation
+//! account.
+
+/// An extra meta field, in the order of the validation account.
+///
+/// Fields of a flattened struct are named `field.inner_field`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetaField {
+    pub name: String,
+    pub optional: bool,
+}
+
+#[cfg(not(target_os = "solana"))]
+pub use host::*;
+
+#[cfg(not(target_os = "solana"))]
+mod host {
+    use std::fmt::Write;
+
+    use solana_program::pubkey::Pubkey;
+    use spl_tlv_account_resolution::seeds::Seed;
+
+    use crate::context::ExtraMetas;
+
+    /// Names of the accounts in the `Execute` instruction, in order.
+    const BASE_ACCOUNT_NAMES: [&str; 5] = [
+        "source",
+        "mint",
+        "destination",
+        "authority",
+        "extra_account_metas",
+    ];
+
+    /// Description of an extra meta, with account indices replaced by names.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct ExtraMetaDescription {
+        pub name: String,
+        pub kind: ExtraMetaKind,
+        pub signer: bool,
+        pub writable: bool,
+        pub optional: bool,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum ExtraMetaKind {
+        /// A fixed address.
+        Pubkey(Pubkey),
+        /// A PDA of the hook program, or of `program` if it is set.
+        Pda {
+            program: Option<String>,
+            seeds: Vec<SeedDescription>,
+        },
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum SeedDescription {
+        Literal(Vec<u8>),
+        InstructionData {
+            offset: u8,
+            length: u8,
+        },
+        AccountKey(String),
+        AccountData {
+            account: String,
+            offset: u8,
+            length: u8,
+        },
+    }
+
+    /// Describes the extra metas of `E`.
+    pub fn describe<'info, E: ExtraMetas<'info>>() -> Vec<ExtraMetaDescription> {
+        let fields = E::meta_fields();
+        let account_name = |index: u8| {
+            let index = index as usize;
+            match BASE_ACCOUNT_NAMES.get(index) {
+                Some(name) => name.to_string(),
+                None => fields
+                    .get(index - BASE_ACCOUNT_NAMES.len())
+                    .map_or_else(|| index.to_string(), |field| field.name.clone()),
+            }
+        };
+
+        E::to_extra_account_metas()
+            .iter()
+            .zip(fields.iter())
+            .map(|(meta, field)| {
+                let kind = match meta.discriminator {
+                    0 => ExtraMetaKind::Pubkey(Pubkey::new_from_array(meta.address_config)),
+                    discriminator => ExtraMetaKind::Pda {
+                        program: (discriminator >= 128).then(|| account_name(discriminator - 128)),
+                        seeds: Seed::unpack_address_config(&meta.address_config)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|seed| match seed {
+                                Seed::Literal { bytes } => SeedDescription::Literal(bytes),
+                                Seed::InstructionData { index, length } => {
+                                    SeedDescription::InstructionData {
+                                        offset: index,
+                                        length,
+                                    }
+                                }
+                                Seed::AccountKey { index } => {
+                                    SeedDescription::AccountKey(account_name(index))
+                                }
+                                Seed::AccountData {
+                                    account_index,
+                                    data_index,
+                                    length,
+                                } => SeedDescription::AccountData {
+                                    account: account_name(account_index),
+                                    offset: data_index,
+                                    length,
+                                },
+                                Seed::Uninitialized => {
+                                    unreachable!("unpacked seeds are initialized")
+                                }
+                            })
+                            .collect(),
+                    },
+                };
+                ExtraMetaDescription {
+                    name: field.name.clone(),
+                    kind,
+                    signer: meta.is_signer.into(),
+                    writable: meta.is_writable.into(),
+                    optional: field.optional,
+                }
+            })
+            .collect()
+    }
+
+    /// Serializes the description of `E`'s extra metas as a JSON array.
+    pub fn to_json<'info, E: ExtraMetas<'info>>() -> String {
+        let metas = describe::<E>()
+            .iter()
+            .map(|meta| {
+                let kind = match &meta.kind {
+                    ExtraMetaKind::Pubkey(pubkey) => format!("\"pubkey\":\"{}\"", pubkey),
+                    ExtraMetaKind::Pda { program, seeds } => format!(
+                        "\"pda\":{{\"program\":{},\"seeds\":[{}]}}",
+                        program.as_deref().map_or("null".to_string(), json_string),
+                        seeds.iter().map(seed_json).collect::<Vec<_>>().join(",")
+                    ),
+                };
+                format!(
+                    "{{\"name\":{},{},\"signer\":{},\"writable\":{},\"optional\":{}}}",
+                    json_string(&meta.name),
+                    kind,
+                    meta.signer,
+                    meta.writable,
+                    meta.optional
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]", metas.join(","))
+    }
+
+    fn seed_json(seed: &SeedDescription) -> String {
+        match seed {
+            SeedDescription::Literal(bytes) => format!(
+                "{{\"literal\":[{}]}}",
+                bytes
+                    .iter()
+                    .map(u8::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            SeedDescription::InstructionData { offset, length } => format!(
+                "{{\"instruction_data\":{{\"offset\":{},\"length\":{}}}}}",
+                offset, length
+            ),
+            SeedDescription::AccountKey(account) => {
+                format!("{{\"account_key\":{}}}", json_string(account))
+            }
+            SeedDescription::AccountData {
+                account,
+                offset,
+                length,
+            } => format!(
+                "{{\"account_data\":{{\"account\":{},\"offset\":{},\"length\":{}}}}}",
+                json_string(account),
+                offset,
+                length
+            ),
+        }
+    }
+
+    fn json_string(value: &str) -> String {
+        let mut json = String::with_capacity(value.len() + 2);
+        json.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                c if c.is_control() => {
+                    let _ = write!(json, "\\u{:04x}", c as u32);
+                }
+                c => json.push(c),
+            }
+        }
+        json.push('"');
+        json
+    }
+}
@@ -0,0 +1,136 @@
+//! Guard macros for transfer-hook policies.
+//!
+//! Each macro returns `Err(error.into())` from the enclosing function when its
+//! condition does not hold, so the error can be a `ProgramError` or a
+//! `#[kaptn_error]` value. The failing condition is logged with its file and
+//! line first.
+
+/// Returns `error` unless `condition` is true.
+#[macro_export]
+macro_rules! require {
+    ($condition:expr, $error:expr $(,)?) => {
+        if !($condition) {
+            $crate::solana_program::msg!(
+                "{}:{}: require!({}) failed",
+                file!(),
+                line!(),
+                stringify!($condition)
+            );
+            return Err($error.into());
+        }
+    };
+}
+
+/// Returns `error` unless `left == right`.
+#[macro_export]
+macro_rules! require_eq {
+    ($left:expr, $right:expr, $error:expr $(,)?) => {
+        $crate::__require_cmp!(require_eq, ==, $left, $right, $error)
+    };
+}
+
+/// Returns `error` if `left == right`.
+#[macro_export]
+macro_rules! require_neq {
+    ($left:expr, $right:expr, $error:expr $(,)?) => {
+        $crate::__require_cmp!(require_neq, !=, $left, $right, $error)
+    };
+}
+
+/// Returns `error` unless `left > right`.
+#[macro_export]
+macro_rules! require_gt {
+    ($left:expr, $right:expr, $error:expr $(,)?) => {
+        $crate::__require_cmp!(require_gt, >, $left, $right, $error)
+    };
+}
+
+/// Returns `error` unless `left >= right`.
+#[macro_export]
+macro_rules! require_gte {
+    ($left:expr, $right:expr, $error:expr $(,)?) => {
+        $crate::__require_cmp!(require_gte, >=, $left, $right, $error)
+    };
+}
+
+/// Returns `error` unless `left < right`.
+#[macro_export]
+macro_rules! require_lt {
+    ($left:expr, $right:expr, $error:expr $(,)?) => {
+        $crate::__require_cmp!(require_lt, <, $left, $right, $error)
+    };
+}
+
+/// Returns `error` unless `left <= right`.
+#[macro_export]
+macro_rules! require_lte {
+    ($left:expr, $right:expr, $error:expr $(,)?) => {
+        $crate::__require_cmp!(require_lte, <=, $left, $right, $error)
+    };
+}
+
+/// Returns `error` unless the two pubkeys are equal, logging both in base58.
+#[macro_export]
+macro_rules! require_keys_eq {
+    ($left:expr, $right:expr, $error:expr $(,)?) => {
+        $crate::__require_keys!(require_keys_eq, ==, $left, $right, $error)
+    };
+}
+
+/// Returns `error` if the two pubkeys are equal.
+#[macro_export]
+macro_rules! require_keys_neq {
+    ($left:expr, $right:expr, $error:expr $(,)?) => {
+        $crate::__require_keys!(require_keys_neq, !=, $left, $right, $error)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_cmp {
+    ($name:ident, $op:tt, $left:expr, $right:expr, $error:expr) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left $op *right) {
+                    $crate::solana_program::msg!(
+                        "{}:{}: {}!({}, {}) failed: left = {:?}, right = {:?}",
+                        file!(),
+                        line!(),
+                        stringify!($name),
+                        stringify!($left),
+                        stringify!($right),
+                        left,
+                        right
+                    );
+                    return Err($error.into());
+                }
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_keys {
+    ($name:ident, $op:tt, $left:expr, $right:expr, $error:expr) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let left: &$crate::solana_program::pubkey::Pubkey = left;
+                let right: &$crate::solana_program::pubkey::Pubkey = right;
+                if !(left $op right) {
+                    $crate::solana_program::msg!(
+                        "{}:{}: {}!({}, {}) failed: left = {}, right = {}",
+                        file!(),
+                        line!(),
+                        stringify!($name),
+                        stringify!($left),
+                        stringify!($right),
+                        left,
+                        right
+                    );
+                    return Err($error.into());
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,139 @@
+//! State accounts owned by the transfer-hook program.
+//!
+//! An `#[account]` struct is stored as an 8 byte discriminator followed by its
+//! Borsh serialization, and is loaded from an extra meta with `StateAccount`.
+//! An `#[account(zero_copy)]` struct is stored as its raw bytes instead, and is
+//! read in place through `ZeroCopyAccount` without being decoded.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use bytemuck::Pod;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// A struct stored in an account of the hook program, implemented by `#[account]`.
+pub trait State: BorshSerialize + BorshDeserialize + Space {
+    /// First 8 bytes of `sha256("account:<StructName>")`.
+    const DISCRIMINATOR: [u8; 8];
+    /// Program that owns accounts of this type, `crate::ID` unless set with
+    /// `#[account(owner = ...)]`.
+    const OWNER: Pubkey;
+    /// Size of the account to allocate, discriminator included.
+    const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Deserializes account data written by `try_to_data`.
+    ///
+    /// Bytes after the serialized struct are ignored, as accounts are
+    /// allocated for the largest value.
+    fn try_from_data(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut payload = data
+            .strip_prefix(&Self::DISCRIMINATOR)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Self::deserialize(&mut payload).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Writes the discriminator and the serialized struct to the start of `data`.
+    fn try_to_data(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        if data.len() < 8 {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let (discriminator, mut payload) = data.split_at_mut(8);
+        discriminator.copy_from_slice(&Self::DISCRIMINATOR);
+        self.serialize(&mut payload)
+            .map_err(|_| ProgramError::AccountDataTooSmall)
+    }
+}
+
+/// A `Pod` struct stored in place in an account of the hook program,
+/// implemented by `#[account(zero_copy)]`.
+pub trait ZeroCopy: Pod {
+    /// First 8 bytes of `sha256("account:<StructName>")`.
+    const DISCRIMINATOR: [u8; 8];
+    /// Program that owns accounts of this type, `crate::ID` unless set with
+    /// `#[account(zero_copy, owner = ...)]`.
+    const OWNER: Pubkey;
+    /// Size of the account to allocate, discriminator included.
+    const LEN: usize = 8 + core::mem::size_of::<Self>();
+
+    /// Casts account data to the struct after checking its discriminator,
+    /// length and alignment.
+    fn from_data(data: &[u8]) -> Result<&Self, ProgramError> {
+        let payload = data
+            .strip_prefix(&Self::DISCRIMINATOR)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        cast(payload)
+    }
+
+    /// Mutable version of `from_data`.
+    fn from_data_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if !data.starts_with(&Self::DISCRIMINATOR) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        cast_mut(&mut data[8..])
+    }
+
+    /// Writes the discriminator to freshly allocated, zeroed account data and
+    /// returns the zeroed struct.
+    fn init_data(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let discriminator = data.get_mut(..8).ok_or(ProgramError::AccountDataTooSmall)?;
+        if discriminator != [0; 8] {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        discriminator.copy_from_slice(&Self::DISCRIMINATOR);
+        cast_mut(&mut data[8..])
+    }
+}
+
+fn cast<T: Pod>(payload: &[u8]) -> Result<&T, ProgramError> {
+    let bytes = payload
+        .get(..core::mem::size_of::<T>())
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    bytemuck::try_from_bytes(bytes).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+fn cast_mut<T: Pod>(payload: &mut [u8]) -> Result<&mut T, ProgramError> {
+    let bytes = payload
+        .get_mut(..core::mem::size_of::<T>())
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    bytemuck::try_from_bytes_mut(bytes).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// Largest Borsh size of a value of the type.
+///
+/// `#[account]` sums it over the fields of the struct, `Vec` and `String`
+/// fields take their maximum length from `#[max_len(n)]`.
+pub trait Space {
+    const INIT_SPACE: usize;
+}
+
+macro_rules! impl_space {
+    ($($ty:ty => $space:expr),* $(,)?) => {
+        $(impl Space for $ty {
+            const INIT_SPACE: usize = $space;
+        })*
+    };
+}
+
+impl_space! {
+    () => 0,
+    bool => 1,
+    u8 => 1,
+    i8 => 1,
+    u16 => 2,
+    i16 => 2,
+    u32 => 4,
+    i32 => 4,
+    f32 => 4,
+    u64 => 8,
+    i64 => 8,
+    f64 => 8,
+    u128 => 16,
+    i128 => 16,
+    Pubkey => 32,
+}
+
+impl<T: Space> Space for Option<T> {
+    const INIT_SPACE: usize = 1 + T::INIT_SPACE;
+}
+
+impl<T: Space, const N: usize> Space for [T; N] {
+    const INIT_SPACE: usize = N * T::INIT_SPACE;
+}
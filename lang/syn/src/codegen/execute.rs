@@ -1,7 +1,58 @@
 use crate::TransferHookInput;
 use quote::quote;
 
-pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
+pub fn generate(program: &TransferHookInput) -> proc_macro2::TokenStream {
+    let mint_check = program.mint_check();
+    let (check_base_accounts_call, check_base_accounts_fn) = if program.skip_base_account_checks {
+        (quote! {}, quote! {})
+    } else {
+        (
+            quote! {
+                check_base_accounts(source_account_info, mint_info, destination_account_info)?;
+            },
+            quote! {
+                /// Checks the source, mint and destination are Token-2022 accounts
+                /// and that both token accounts belong to the mint.
+                fn check_base_accounts<'info>(
+                    source_account_info: &AccountInfo<'info>,
+                    mint_info: &AccountInfo<'info>,
+                    destination_account_info: &AccountInfo<'info>,
+                ) -> Result<(), ProgramError> {
+                    for (name, info) in [
+                        ("source", source_account_info),
+                        ("mint", mint_info),
+                        ("destination", destination_account_info),
+                    ] {
+                        if *info.owner != spl_token_2022::id() {
+                            msg!("The {} account {} is not owned by Token-2022", name, info.key);
+                            return Err(ProgramError::IllegalOwner);
+                        }
+                    }
+
+                    StateWithExtensions::<Mint>::unpack(&mint_info.try_borrow_data()?)?;
+                    for (name, info) in [
+                        ("source", source_account_info),
+                        ("destination", destination_account_info),
+                    ] {
+                        let data = info.try_borrow_data()?;
+                        let account = StateWithExtensions::<Account>::unpack(&data)?;
+                        if account.base.mint != *mint_info.key {
+                            msg!(
+                                "The {} account {} belongs to mint {}, not {}",
+                                name,
+                                info.key,
+                                account.base.mint,
+                                mint_info.key
+                            );
+                            return Err(KaptnError::TokenAccountMintMismatch.into());
+                        }
+                    }
+                    Ok(())
+                }
+            },
+        )
+    };
+
     quote! {
         pub fn process_execute<'info, E: ExtraMetas<'info>>(
             program_id: &Pubkey,
@@ -17,6 +68,12 @@ pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
             let authority_info = next_account_info(account_info_iter)?;
             let extra_account_metas_info = next_account_info(account_info_iter)?;
 
+            if !#mint_check(mint_info.key) {
+                msg!("Mint {} is not accepted by this transfer hook", mint_info.key);
+                return Err(KaptnError::InvalidMint.into());
+            }
+
+            #check_base_accounts_call
             check_token_account_is_transferring(source_account_info)?;
             check_token_account_is_transferring(destination_account_info)?;
 
@@ -25,31 +82,29 @@ pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
                 return Err(ProgramError::InvalidSeeds);
             }
 
-            let data = extra_account_metas_info.try_borrow_data()?;
-
-            ExtraAccountMetaList::check_account_infos::<ExecuteInstruction>(
-                accounts,
-                &TransferHookInstruction::Execute { amount }.pack(),
-                program_id,
-                &data,
-            )?;
+            check_extra_account_metas::<E>(&extra_account_metas_info.try_borrow_data()?)?;
 
-            let extra_metas = E::from_accounts(accounts)?;
-
-            let ctx = TransferContext {
-                program_id,
-                source_account: source_account_info,
-                mint: mint_info,
-                destination_account: destination_account_info,
-                authority: authority_info,
-                extra_account_metas: extra_account_metas_info,
-                amount,
-                extra_metas,
-            };
+            // Every extra meta is resolved once, and bound to the account passed
+            // with its address, while the struct is built
+            let mut resolver = MetaResolver::new(program_id, accounts, amount)?;
+            let extra_metas = E::from_resolver(&mut resolver)?;
+            let ctx = TransferContext::from_resolver(resolver, extra_metas)?;
 
             process_transfer(ctx)
         }
 
+        /// Checks the validation account holds the extra metas of `E`, which
+        /// the accounts are then resolved against.
+        fn check_extra_account_metas<'info, E: ExtraMetas<'info>>(data: &[u8]) -> ProgramResult {
+            let state = TlvStateBorrowed::unpack(data)?;
+            let extra_meta_list = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&state)?;
+            if extra_meta_list.data() != E::to_extra_account_metas().as_slice() {
+                msg!("The validation account does not hold the extra metas of the hook");
+                return Err(KaptnError::ExtraMetasMismatch.into());
+            }
+            Ok(())
+        }
+
         fn check_token_account_is_transferring(account_info: &AccountInfo) -> Result<(), ProgramError> {
             let account_data = account_info.try_borrow_data()?;
             let token_account = StateWithExtensions::<Account>::unpack(&account_data)?;
@@ -61,5 +116,7 @@ pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
             }
         }
 
+        #check_base_accounts_fn
+
     }
 }
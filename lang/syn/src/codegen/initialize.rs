@@ -1,7 +1,9 @@
 use crate::TransferHookInput;
 use quote::quote;
 
-pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
+pub fn generate(program: &TransferHookInput) -> proc_macro2::TokenStream {
+    let mint_check = program.mint_check();
+
     quote! {
         pub fn process_initialize_extra_account_meta_list(
             program_id: &Pubkey,
@@ -15,7 +17,7 @@ pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
             let authority_info = next_account_info(account_info_iter)?;
             let _system_program_info = next_account_info(account_info_iter)?;
 
-            if !check_mint(mint_info.key) {
+            if !#mint_check(mint_info.key) {
                 return Err(ProgramError::InvalidArgument);
             }
 
@@ -45,12 +47,12 @@ pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
             let account_size = ExtraAccountMetaList::size_of(length)?;
             invoke_signed(
                 &system_instruction::allocate(extra_account_metas_info.key, account_size as u64),
-                &[extra_account_metas_info.clone()],
+                core::slice::from_ref(extra_account_metas_info),
                 &[&signer_seeds],
             )?;
             invoke_signed(
                 &system_instruction::assign(extra_account_metas_info.key, program_id),
-                &[extra_account_metas_info.clone()],
+                core::slice::from_ref(extra_account_metas_info),
                 &[&signer_seeds],
             )?;
 
@@ -1,7 +1,23 @@
 use crate::TransferHookInput;
 use quote::quote;
 
-pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
+pub fn generate(program: &TransferHookInput) -> proc_macro2::TokenStream {
+    // Custom instructions are matched on their discriminator before the interface
+    let instructions = &program.instructions;
+    let dispatch = (!instructions.is_empty()).then(|| {
+        quote! {
+            if instruction_data.len() >= 8 {
+                let (discriminator, data) = instruction_data.split_at(8);
+                #(
+                    if discriminator == #instructions::DISCRIMINATOR {
+                        msg!("Instruction: {}", #instructions::NAME);
+                        return #instructions::process(program_id, accounts, data);
+                    }
+                )*
+            }
+        }
+    });
+
     quote! {
         fn process_instruction<'info, E: ExtraMetas<'info>>(
             program_id: &Pubkey,
@@ -9,6 +25,8 @@ pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
             instruction_data: &[u8],
             process_transfer: fn(TransferContext<'_, 'info, E>) -> ProgramResult,
         ) -> ProgramResult {
+            #dispatch
+
             let instruction = TransferHookInstruction::unpack(instruction_data)?;
 
             match instruction {
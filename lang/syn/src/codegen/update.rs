@@ -1,7 +1,9 @@
 use crate::TransferHookInput;
 use quote::quote;
 
-pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
+pub fn generate(program: &TransferHookInput) -> proc_macro2::TokenStream {
+    let mint_check = program.mint_check();
+
     quote! {
         pub fn process_update_extra_account_meta_list(
             program_id: &Pubkey,
@@ -14,6 +16,10 @@ pub fn generate(_program: &TransferHookInput) -> proc_macro2::TokenStream {
             let mint_info = next_account_info(account_info_iter)?;
             let authority_info = next_account_info(account_info_iter)?;
 
+            if !#mint_check(mint_info.key) {
+                return Err(ProgramError::InvalidArgument);
+            }
+
             let mint_data = mint_info.try_borrow_data()?;
             let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
             let mint_authority = mint
@@ -4,12 +4,30 @@ use syn::ItemFn;
 
 mod codegen;
 mod parse;
+pub mod utils;
 
 pub use parse::parse_transfer_hook_input;
 
 pub struct TransferHookInput {
     pub fn_name: syn::Ident,
     pub item_fn: ItemFn,
+    /// `#[instruction]` functions listed in `#[transfer_hook(instructions(...))]`.
+    pub instructions: Vec<syn::Path>,
+    /// `fn(&Pubkey) -> bool` accepting the mints the hook runs for, set with
+    /// `mint_check = ...`. Defaults to `check_mint` from `declare_mint!`.
+    pub mint_check: Option<syn::Path>,
+    /// Set by `skip_base_account_checks` to leave out the owner and mint checks
+    /// of the source, mint and destination accounts in `Execute`.
+    pub skip_base_account_checks: bool,
+}
+
+impl TransferHookInput {
+    pub fn mint_check(&self) -> proc_macro2::TokenStream {
+        match &self.mint_check {
+            Some(path) => quote! { #path },
+            None => quote! { check_mint },
+        }
+    }
 }
 
 pub fn generate_transfer_hook_code(input: TransferHookInput) -> TokenStream {
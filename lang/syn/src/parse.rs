@@ -1,9 +1,65 @@
 use crate::TransferHookInput;
 use proc_macro2::TokenStream;
-use syn::{parse2, ItemFn};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse2,
+    punctuated::Punctuated,
+    Ident, ItemFn, Path, Token,
+};
 
-pub fn parse_transfer_hook_input(item: TokenStream) -> TransferHookInput {
-    let item_fn = parse2::<ItemFn>(item).expect("Failed to parse function");
+/// Arguments of `#[transfer_hook(...)]`.
+struct TransferHookArgs {
+    instructions: Vec<Path>,
+    mint_check: Option<Path>,
+    skip_base_account_checks: bool,
+}
+
+impl Parse for TransferHookArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = TransferHookArgs {
+            instructions: vec![],
+            mint_check: None,
+            skip_base_account_checks: false,
+        };
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key == "instructions" {
+                let content;
+                syn::parenthesized!(content in input);
+                args.instructions
+                    .extend(Punctuated::<Path, Token![,]>::parse_terminated(&content)?);
+            } else if key == "mint_check" && args.mint_check.is_none() {
+                input.parse::<Token![=]>()?;
+                args.mint_check = Some(input.parse()?);
+            } else if key == "skip_base_account_checks" && !args.skip_base_account_checks {
+                args.skip_base_account_checks = true;
+            } else {
+                return Err(syn::Error::new_spanned(
+                    key,
+                    "unknown or repeated argument, expected `instructions(...)`, \
+                     `mint_check = <fn>` or `skip_base_account_checks`",
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+pub fn parse_transfer_hook_input(
+    attr: TokenStream,
+    item: TokenStream,
+) -> syn::Result<TransferHookInput> {
+    let args = parse2::<TransferHookArgs>(attr)?;
+    let item_fn = parse2::<ItemFn>(item)?;
     let fn_name = item_fn.sig.ident.clone();
-    TransferHookInput { fn_name, item_fn }
+    Ok(TransferHookInput {
+        fn_name,
+        item_fn,
+        instructions: args.instructions,
+        mint_check: args.mint_check,
+        skip_base_account_checks: args.skip_base_account_checks,
+    })
 }
@@ -0,0 +1,76 @@
+//! Parsing helpers shared by the attribute and derive macros.
+
+use proc_macro2::Span;
+use quote::format_ident;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Expr, Field, GenericParam, Generics, Ident, Lifetime, LifetimeDef, Token,
+};
+
+/// A single `key` or `key = value` argument of a helper attribute such as
+/// `#[meta(...)]` or `#[accounts(...)]`.
+pub struct AttrArg {
+    pub key: Ident,
+    pub value: Option<Expr>,
+}
+
+impl Parse for AttrArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(AttrArg { key, value })
+    }
+}
+
+/// The comma separated arguments of a helper attribute.
+pub struct AttrArgs(pub Punctuated<AttrArg, Token![,]>);
+
+impl Parse for AttrArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(AttrArgs(input.parse_terminated(AttrArg::parse)?))
+    }
+}
+
+/// The accounts lifetime of a struct and the generics to implement its traits with.
+///
+/// The accounts lifetime is the struct's first lifetime, whatever its name.
+/// Structs without one get a fresh lifetime on the impl.
+pub fn info_lifetime(generics: &Generics) -> (Lifetime, Generics) {
+    let mut impl_generics = generics.clone();
+    let lifetime = match generics.lifetimes().next() {
+        Some(def) => def.lifetime.clone(),
+        None => {
+            let lifetime = Lifetime::new("'__info", Span::call_site());
+            impl_generics.params.insert(
+                0,
+                GenericParam::Lifetime(LifetimeDef::new(lifetime.clone())),
+            );
+            lifetime
+        }
+    };
+    (lifetime, impl_generics)
+}
+
+/// The binding and the name of the field at `index`.
+///
+/// Tuple fields are loaded into `__field0`, `__field1`, ... and named by their index.
+pub fn field_binding(index: usize, field: &Field) -> (Ident, String) {
+    match &field.ident {
+        Some(ident) => (ident.clone(), ident.to_string()),
+        None => (format_ident!("__field{}", index), index.to_string()),
+    }
+}
+
+/// Collects errors so all of them are reported at once.
+pub fn combine_error(errors: &mut Option<syn::Error>, err: syn::Error) {
+    match errors {
+        Some(errors) => errors.combine(err),
+        None => *errors = Some(err),
+    }
+}
@@ -0,0 +1,26 @@
+use kaptn_lang::prelude::*;
+
+mod common;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+declare_mint!("A7yUYJNEVYRLE4QWsnc9rE9JRsm7DfqEmLscQVwkffAk");
+
+#[transfer_hook(skip_base_account_checks)]
+pub fn hook(_ctx: TransferContext<()>) -> ProgramResult {
+    Ok(())
+}
+
+#[test]
+fn test_skip_base_account_checks() {
+    let mut accounts = execute_accounts(&ID, &MINT, false);
+    accounts[0].owner = Pubkey::new_unique();
+
+    // Without the checks a source not owned by Token-2022 is only rejected
+    // once its transferring flag is read
+    let execute = TransferHookInstruction::Execute { amount: 0 }.pack();
+    assert_eq!(
+        __process_instruction(&ID, &account_infos(&mut accounts), &execute),
+        Err(TransferHookError::ProgramCalledOutsideOfTransfer.into())
+    );
+}
@@ -0,0 +1,126 @@
+//! Accounts shared by the integration tests, each test uses a subset of them.
+#![allow(dead_code)]
+
+use kaptn_lang::prelude::*;
+use kaptn_lang::solana_program::program_pack::Pack;
+
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+impl TestAccount {
+    /// A read-only account with a unique key, owned by Token-2022.
+    pub fn new(data: Vec<u8>) -> Self {
+        Self::owned_by(spl_token_2022::id(), data)
+    }
+
+    /// A read-only account with a unique key.
+    pub fn owned_by(owner: Pubkey, data: Vec<u8>) -> Self {
+        Self {
+            key: Pubkey::new_unique(),
+            owner,
+            is_signer: false,
+            is_writable: false,
+            lamports: 0,
+            data,
+        }
+    }
+}
+
+pub fn account_infos(accounts: &mut [TestAccount]) -> Vec<AccountInfo<'_>> {
+    accounts
+        .iter_mut()
+        .map(|account| {
+            AccountInfo::new(
+                &account.key,
+                account.is_signer,
+                account.is_writable,
+                &mut account.lamports,
+                &mut account.data,
+                &account.owner,
+                false,
+                0,
+            )
+        })
+        .collect()
+}
+
+/// An initialized token account without a delegate.
+pub fn new_token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..Account::default()
+    }
+}
+
+pub fn token_account_data(account: Account) -> Vec<u8> {
+    let mut data = vec![0; Account::LEN];
+    let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+    state.base = account;
+    state.pack_base();
+    data
+}
+
+/// A token account with the `TransferHookAccount` extension, as Token-2022
+/// passes it to the hook during a transfer when `transferring` is set.
+pub fn hook_token_account_data(account: Account, transferring: bool) -> Vec<u8> {
+    let size =
+        ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::TransferHookAccount])
+            .unwrap();
+    let mut data = vec![0; size];
+    let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+    let extension = state.init_extension::<TransferHookAccount>(true).unwrap();
+    extension.transferring = transferring.into();
+    state.base = account;
+    state.pack_base();
+    state.init_account_type().unwrap();
+    data
+}
+
+/// An initialized mint without authorities.
+pub fn new_mint(supply: u64, decimals: u8) -> Mint {
+    Mint {
+        supply,
+        decimals,
+        is_initialized: true,
+        ..Mint::default()
+    }
+}
+
+pub fn mint_data(mint: Mint) -> Vec<u8> {
+    let mut data = vec![0; Mint::LEN];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    state.base = mint;
+    state.pack_base();
+    data
+}
+
+/// The base accounts of an `Execute` of `mint`, signed by the source's owner.
+pub fn execute_accounts(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    transferring: bool,
+) -> Vec<TestAccount> {
+    let owner = Pubkey::new_unique();
+    let source = new_token_account(mint, &owner, 100);
+    let destination = new_token_account(mint, &Pubkey::new_unique(), 0);
+    let mut accounts = vec![
+        TestAccount::new(hook_token_account_data(source, transferring)),
+        TestAccount::new(mint_data(new_mint(1_000, 6))),
+        TestAccount::new(hook_token_account_data(destination, transferring)),
+        TestAccount::owned_by(Pubkey::default(), vec![]),
+        TestAccount::owned_by(*program_id, vec![]),
+    ];
+    accounts[1].key = *mint;
+    accounts[3].key = owner;
+    accounts[4].key = get_extra_account_metas_address(mint, program_id);
+    accounts
+}
@@ -0,0 +1,8 @@
+//! Misuses of the macros that must fail to compile, with the expected errors in `ui/*.stderr`.
+//! Run with `TRYBUILD=overwrite` to update the expected errors after changing a message.
+
+#[test]
+fn test_compile_fail() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
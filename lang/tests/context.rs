@@ -0,0 +1,305 @@
+use kaptn_lang::prelude::*;
+use kaptn_lang::solana_program::{
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+};
+use spl_token_2022::extension::{
+    permanent_delegate::PermanentDelegate,
+    transfer_fee::{TransferFee, TransferFeeConfig},
+};
+use std::sync::Mutex;
+
+mod common;
+use common::*;
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([9; 32]);
+
+#[derive(ExtraMetas)]
+pub struct SigningMetas<'info> {
+    #[meta(pubkey = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")]
+    pub token_program: AccountInfo<'info>,
+    #[meta(seeds = [literal(b"vault"), owner_of(source)])]
+    pub vault: AccountInfo<'info>,
+    #[meta(seeds = [account_key(vault), amount])]
+    pub receipt: AccountInfo<'info>,
+}
+
+/// Addresses the hook signed for in `invoke_signed`, recorded by `SignerStubs`.
+static SIGNERS: Mutex<Vec<Pubkey>> = Mutex::new(vec![]);
+
+struct SignerStubs;
+
+impl SyscallStubs for SignerStubs {
+    fn sol_invoke_signed(
+        &self,
+        _instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let mut signers = SIGNERS.lock().unwrap();
+        for seeds in signers_seeds {
+            signers.push(Pubkey::create_program_address(seeds, &PROGRAM_ID).unwrap());
+        }
+        Ok(())
+    }
+}
+
+/// A mint whose transfer fee goes from 1% to 5% at epoch 10.
+fn mint_with_fee_data(mint: Mint) -> Vec<u8> {
+    let size =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let mut data = vec![0; size];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    config.older_transfer_fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: u64::MAX.into(),
+        transfer_fee_basis_points: 100.into(),
+    };
+    config.newer_transfer_fee = TransferFee {
+        epoch: 10.into(),
+        maximum_fee: 100_000.into(),
+        transfer_fee_basis_points: 500.into(),
+    };
+    state.base = mint;
+    state.pack_base();
+    state.init_account_type().unwrap();
+    data
+}
+
+fn mint_with_permanent_delegate_data(mint: Mint, delegate: &Pubkey) -> Vec<u8> {
+    let size =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::PermanentDelegate])
+            .unwrap();
+    let mut data = vec![0; size];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let extension = state.init_extension::<PermanentDelegate>(true).unwrap();
+    extension.delegate = Some(*delegate).try_into().unwrap();
+    state.base = mint;
+    state.pack_base();
+    state.init_account_type().unwrap();
+    data
+}
+
+struct Transfer {
+    mint: Pubkey,
+    sender: Pubkey,
+    receiver: Pubkey,
+    delegate: Pubkey,
+}
+
+impl Transfer {
+    fn new() -> Self {
+        Self {
+            mint: Pubkey::new_unique(),
+            sender: Pubkey::new_unique(),
+            receiver: Pubkey::new_unique(),
+            delegate: Pubkey::new_unique(),
+        }
+    }
+
+    /// The base accounts of an `Execute` of 2.5 tokens after the transfer.
+    fn accounts(&self) -> Vec<TestAccount> {
+        let source = Account {
+            mint: self.mint,
+            owner: self.sender,
+            amount: 7_500_000,
+            delegate: COption::Some(self.delegate),
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        let destination = Account {
+            mint: self.mint,
+            owner: self.receiver,
+            amount: 2_500_000,
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        let mint = Mint {
+            supply: 10_000_000,
+            decimals: 6,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        vec![
+            TestAccount::new(token_account_data(source)),
+            TestAccount::new(mint_data(mint)),
+            TestAccount::new(token_account_data(destination)),
+            TestAccount::new(vec![]),
+            TestAccount::new(vec![]),
+        ]
+    }
+}
+
+#[test]
+fn test_decoded_accounts() {
+    let transfer = Transfer::new();
+    let mut accounts = transfer.accounts();
+    let accounts = account_infos(&mut accounts);
+    let ctx = TransferContext::new(&PROGRAM_ID, &accounts, 2_500_000, (), &[]).unwrap();
+
+    assert_eq!(ctx.source_owner(), Ok(transfer.sender));
+    assert_eq!(ctx.destination_owner(), Ok(transfer.receiver));
+    assert_eq!(ctx.source_balance(), Ok(7_500_000));
+    assert_eq!(ctx.destination_balance(), Ok(2_500_000));
+    assert_eq!(ctx.source_delegate(), Ok(Some(transfer.delegate)));
+    assert_eq!(ctx.decimals(), Ok(6));
+    assert_eq!(ctx.supply(), Ok(10_000_000));
+    assert_eq!(ctx.ui_amount(), Ok(2.5));
+    assert_eq!(ctx.mint_account().unwrap().supply, 10_000_000);
+}
+
+#[test]
+fn test_decoded_accounts_are_cached() {
+    let transfer = Transfer::new();
+    let mut accounts = transfer.accounts();
+    let accounts = account_infos(&mut accounts);
+    let ctx = TransferContext::new(&PROGRAM_ID, &accounts, 2_500_000, (), &[]).unwrap();
+
+    assert_eq!(ctx.source_balance(), Ok(7_500_000));
+    accounts[0].try_borrow_mut_data().unwrap().fill(0);
+    assert_eq!(ctx.source_balance(), Ok(7_500_000));
+}
+
+#[test]
+fn test_decoding_errors() {
+    let transfer = Transfer::new();
+    let mut accounts = transfer.accounts();
+    accounts[0].owner = Pubkey::new_unique();
+    accounts[1].data = vec![0; Mint::LEN];
+    accounts[2].data = vec![1; 10];
+    let accounts = account_infos(&mut accounts);
+    let ctx = TransferContext::new(&PROGRAM_ID, &accounts, 2_500_000, (), &[]).unwrap();
+
+    assert_eq!(ctx.source_owner(), Err(ProgramError::IllegalOwner));
+    assert_eq!(ctx.decimals(), Err(ProgramError::UninitializedAccount));
+    assert_eq!(
+        ctx.destination_owner(),
+        Err(ProgramError::InvalidAccountData)
+    );
+
+    assert!(TransferContext::new(&PROGRAM_ID, &accounts[..4], 0, (), &[]).is_err());
+}
+
+#[test]
+fn test_transfer_fee() {
+    let transfer = Transfer::new();
+    let mut accounts = transfer.accounts();
+    let mint = Mint::unpack(&accounts[1].data).unwrap();
+
+    // Without the extension there is no fee
+    {
+        let accounts = account_infos(&mut accounts);
+        let ctx = TransferContext::new(&PROGRAM_ID, &accounts, 2_500_000, (), &[]).unwrap();
+        assert_eq!(ctx.transfer_fee_config(), Ok(None));
+        assert_eq!(ctx.transfer_fee_at_epoch(20), Ok(0));
+        assert_eq!(ctx.net_amount_at_epoch(20), Ok(2_500_000));
+    }
+
+    accounts[1].data = mint_with_fee_data(mint);
+    let accounts = account_infos(&mut accounts);
+    let ctx = TransferContext::new(&PROGRAM_ID, &accounts, 2_500_000, (), &[]).unwrap();
+    assert!(ctx.transfer_fee_config().unwrap().is_some());
+    assert_eq!(ctx.transfer_fee_at_epoch(5), Ok(25_000));
+    assert_eq!(ctx.net_amount_at_epoch(5), Ok(2_475_000));
+
+    // The newer fee applies from its epoch, capped at its maximum
+    assert_eq!(ctx.transfer_fee_at_epoch(10), Ok(100_000));
+    assert_eq!(ctx.net_amount_at_epoch(10), Ok(2_400_000));
+    assert_eq!(ctx.decimals(), Ok(6));
+}
+
+#[test]
+fn test_authority_kind() {
+    let transfer = Transfer::new();
+    let issuer = Pubkey::new_unique();
+    let mut accounts = transfer.accounts();
+
+    let kind_of = |accounts: &mut [TestAccount], authority: Pubkey| {
+        accounts[3].key = authority;
+        let accounts = account_infos(accounts);
+        let ctx = TransferContext::new(&PROGRAM_ID, &accounts, 2_500_000, (), &[]).unwrap();
+        ctx.authority_kind()
+    };
+
+    assert_eq!(
+        kind_of(&mut accounts, transfer.sender),
+        Ok(AuthorityKind::Owner)
+    );
+    assert_eq!(
+        kind_of(&mut accounts, transfer.delegate),
+        Ok(AuthorityKind::Delegate)
+    );
+    assert_eq!(kind_of(&mut accounts, issuer), Ok(AuthorityKind::Unknown));
+
+    // A delegate that spent its whole allowance is cleared before the hook runs
+    let mut source = Account::unpack(&accounts[0].data).unwrap();
+    source.delegate = COption::None;
+    accounts[0].data = token_account_data(source);
+    assert_eq!(
+        kind_of(&mut accounts, transfer.delegate),
+        Ok(AuthorityKind::Unknown)
+    );
+
+    let mint = Mint::unpack(&accounts[1].data).unwrap();
+    accounts[1].data = mint_with_permanent_delegate_data(mint, &issuer);
+    assert_eq!(
+        kind_of(&mut accounts, issuer),
+        Ok(AuthorityKind::PermanentDelegate)
+    );
+    assert_eq!(
+        kind_of(&mut accounts, transfer.sender),
+        Ok(AuthorityKind::Owner)
+    );
+}
+
+#[test]
+fn test_signer_seeds() {
+    let transfer = Transfer::new();
+    let amount = 2_500_000u64;
+    let (vault, vault_bump) =
+        Pubkey::find_program_address(&[b"vault", transfer.sender.as_ref()], &PROGRAM_ID);
+    let (receipt, receipt_bump) =
+        Pubkey::find_program_address(&[vault.as_ref(), &amount.to_le_bytes()], &PROGRAM_ID);
+
+    // The extra metas are passed out of order, seeds still use the validation order
+    let mut accounts = transfer.accounts();
+    for key in [receipt, spl_token_2022::id(), vault] {
+        let mut account = TestAccount::new(vec![]);
+        account.key = key;
+        accounts.push(account);
+    }
+    let accounts = account_infos(&mut accounts);
+    let extra_metas = SigningMetas::from_accounts(&PROGRAM_ID, &accounts, amount).unwrap();
+    let ctx = TransferContext::new(&PROGRAM_ID, &accounts, amount, extra_metas, &[]).unwrap();
+
+    let seeds = SigningMetas::vault_signer_seeds(&ctx).unwrap();
+    assert_eq!(
+        seeds.as_slices(),
+        [b"vault".as_ref(), transfer.sender.as_ref(), &[vault_bump]]
+    );
+    let seeds = SigningMetas::receipt_signer_seeds(&ctx).unwrap();
+    assert_eq!(seeds.bump(), receipt_bump);
+    assert_eq!(
+        seeds.as_slices(),
+        [vault.as_ref(), &amount.to_le_bytes(), &[receipt_bump]]
+    );
+    assert_eq!(SigningMetas::receipt_signer_seeds(&ctx), Ok(seeds.clone()));
+
+    // Built from the resolver, the context reuses the seeds found while resolving
+    let mut resolver = MetaResolver::new(&PROGRAM_ID, &accounts, amount).unwrap();
+    let extra_metas = SigningMetas::from_resolver(&mut resolver).unwrap();
+    let resolved_ctx = TransferContext::from_resolver(resolver, extra_metas).unwrap();
+    assert_eq!(SigningMetas::receipt_signer_seeds(&resolved_ctx), Ok(seeds));
+
+    assert_eq!(ctx.signer_seeds(5), Err(ProgramError::InvalidSeeds));
+    assert_eq!(ctx.signer_seeds(8), Err(ProgramError::InvalidArgument));
+
+    set_syscall_stubs(Box::new(SignerStubs));
+    let instruction = Instruction::new_with_bytes(PROGRAM_ID, &[], vec![]);
+    let seeds = SigningMetas::receipt_signer_seeds(&ctx).unwrap();
+    assert_eq!(seeds.invoke_signed(&instruction, &[]), Ok(()));
+    assert_eq!(*SIGNERS.lock().unwrap(), [receipt]);
+}
@@ -0,0 +1,89 @@
+use kaptn_lang::prelude::*;
+use kaptn_lang::solana_program::decode_error::DecodeError;
+
+#[kaptn_error]
+pub enum HookError {
+    #[msg("The transfer amount is too large")]
+    AmountTooLarge,
+    #[msg("The destination is frozen")]
+    DestinationFrozen,
+    /// Codes can be pinned, later variants count on from them.
+    #[msg("The oracle is stale")]
+    StaleOracle = 6100,
+    Unlabeled,
+}
+
+#[kaptn_error(offset = 7000)]
+pub enum OtherError {
+    #[msg("Other")]
+    Other,
+}
+
+fn check_amount(amount: u64) -> ProgramResult {
+    if amount > 1_000 {
+        return Err(HookError::AmountTooLarge.into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_codes() {
+    assert_eq!(HookError::AmountTooLarge.code(), 6000);
+    assert_eq!(HookError::DestinationFrozen.code(), 6001);
+    assert_eq!(HookError::StaleOracle.code(), 6100);
+    assert_eq!(HookError::Unlabeled.code(), 6101);
+    assert_eq!(OtherError::Other.code(), 7000);
+
+    assert_eq!(
+        HookError::CODES,
+        &[
+            (6000, "AmountTooLarge", "The transfer amount is too large"),
+            (6001, "DestinationFrozen", "The destination is frozen"),
+            (6100, "StaleOracle", "The oracle is stale"),
+            (6101, "Unlabeled", "Unlabeled"),
+        ]
+    );
+}
+
+#[test]
+fn test_messages() {
+    assert_eq!(HookError::StaleOracle.name(), "StaleOracle");
+    assert_eq!(HookError::StaleOracle.message(), "The oracle is stale");
+    assert_eq!(HookError::Unlabeled.message(), "Unlabeled");
+    assert_eq!(
+        HookError::DestinationFrozen.to_string(),
+        "The destination is frozen"
+    );
+}
+
+#[test]
+fn test_program_error() {
+    assert_eq!(check_amount(10), Ok(()));
+    assert_eq!(check_amount(2_000), Err(ProgramError::Custom(6000)));
+    assert_eq!(
+        ProgramError::from(HookError::StaleOracle),
+        ProgramError::Custom(6100)
+    );
+}
+
+#[test]
+fn test_decode() {
+    assert_eq!(
+        HookError::from_code(6001),
+        Some(HookError::DestinationFrozen)
+    );
+    assert_eq!(HookError::from_code(6002), None);
+    assert_eq!(
+        <HookError as DecodeError<HookError>>::decode_custom_error_to_enum(6100),
+        Some(HookError::StaleOracle)
+    );
+    assert_eq!(
+        <HookError as DecodeError<HookError>>::decode_custom_error_to_enum(2000),
+        None
+    );
+    assert_eq!(
+        <HookError as DecodeError<HookError>>::type_of(),
+        "HookError"
+    );
+    HookError::StaleOracle.print::<HookError>();
+}
@@ -0,0 +1,91 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use kaptn_lang::event::{decode_event, decode_events};
+use kaptn_lang::prelude::*;
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferChecked {
+    pub source_owner: Pubkey,
+    pub amount: u64,
+    pub memo: String,
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct Paused;
+
+fn transfer_checked() -> TransferChecked {
+    TransferChecked {
+        source_owner: Pubkey::new_from_array([7; 32]),
+        amount: 100,
+        memo: "ahoy".to_string(),
+    }
+}
+
+fn data_log(event: &impl Event) -> String {
+    format!("Program data: {}", STANDARD.encode(event.data()))
+}
+
+#[test]
+fn test_discriminator() {
+    let hash = kaptn_lang::solana_program::hash::hash(b"event:TransferChecked");
+    assert_eq!(TransferChecked::DISCRIMINATOR, hash.to_bytes()[..8]);
+    assert_ne!(TransferChecked::DISCRIMINATOR, Paused::DISCRIMINATOR);
+}
+
+#[test]
+fn test_data() {
+    let event = transfer_checked();
+    let data = event.data();
+    assert_eq!(data[..8], TransferChecked::DISCRIMINATOR);
+    assert_eq!(data[8..40], [7; 32]);
+    assert_eq!(data[40..48], 100u64.to_le_bytes());
+    assert_eq!(TransferChecked::try_from_data(&data), Some(event));
+    assert_eq!(Paused::try_from_data(&data), None);
+    assert_eq!(TransferChecked::try_from_data(&data[..20]), None);
+    assert_eq!(Paused.data(), Paused::DISCRIMINATOR);
+
+    emit!(transfer_checked());
+}
+
+#[test]
+fn test_decode_event() {
+    let log = data_log(&transfer_checked());
+    assert_eq!(decode_event(&log), Some(transfer_checked()));
+    assert_eq!(decode_event::<Paused>(&log), None);
+    assert_eq!(decode_event::<Paused>("Program log: ahoy"), None);
+    assert_eq!(decode_event::<Paused>("Program data: not base64!"), None);
+}
+
+#[test]
+fn test_decode_events() {
+    let program_id = Pubkey::new_unique();
+    let other_id = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        "Program log: Instruction: Execute".to_string(),
+        data_log(&transfer_checked()),
+        "Program log: invoke [1] is not an invocation".to_string(),
+        format!("Program {} invoke [2]", other_id),
+        data_log(&transfer_checked()),
+        format!("Program {} success", other_id),
+        data_log(&Paused),
+        data_log(&transfer_checked()),
+        format!(
+            "Program {} consumed 2000 of 200000 compute units",
+            program_id
+        ),
+        format!("Program {} success", program_id),
+        data_log(&transfer_checked()),
+    ];
+
+    assert_eq!(
+        decode_events::<TransferChecked, _>(&program_id, &logs),
+        vec![transfer_checked(), transfer_checked()]
+    );
+    assert_eq!(decode_events::<Paused, _>(&program_id, &logs), vec![Paused]);
+    assert_eq!(
+        decode_events::<TransferChecked, _>(&other_id, &logs),
+        vec![transfer_checked()]
+    );
+}
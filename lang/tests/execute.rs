@@ -0,0 +1,116 @@
+use kaptn_lang::prelude::*;
+
+mod common;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+declare_mint!("A7yUYJNEVYRLE4QWsnc9rE9JRsm7DfqEmLscQVwkffAk");
+
+const ORACLE_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+#[transfer_hook]
+pub fn hook(_ctx: TransferContext<ExecuteMetas>) -> ProgramResult {
+    Ok(())
+}
+
+#[derive(ExtraMetas)]
+pub struct ExecuteMetas<'info> {
+    #[meta(pubkey = ORACLE_ID)]
+    pub oracle: AccountInfo<'info>,
+    #[meta(seeds = [literal(b"counter"), account_key(source)], writable = true)]
+    pub counter: AccountInfo<'info>,
+}
+
+fn init_validation_account(account: &mut TestAccount, metas: &[ExtraAccountMeta]) {
+    account.data = vec![0; ExtraAccountMetaList::size_of(metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut account.data, metas).unwrap();
+}
+
+fn execute(accounts: &mut [TestAccount]) -> ProgramResult {
+    let data = TransferHookInstruction::Execute { amount: 0 }.pack();
+    __process_instruction(&ID, &account_infos(accounts), &data)
+}
+
+#[test]
+fn test_base_account_checks() {
+    let mut accounts = execute_accounts(&ID, &MINT, false);
+    assert_eq!(
+        execute(&mut accounts),
+        Err(TransferHookError::ProgramCalledOutsideOfTransfer.into())
+    );
+
+    accounts[0].owner = Pubkey::new_unique();
+    assert_eq!(execute(&mut accounts), Err(ProgramError::IllegalOwner));
+
+    let mut accounts = execute_accounts(&ID, &MINT, false);
+    let other_mint = Pubkey::new_unique();
+    accounts[2].data = hook_token_account_data(
+        new_token_account(&other_mint, &Pubkey::new_unique(), 0),
+        false,
+    );
+    assert_eq!(
+        execute(&mut accounts),
+        Err(KaptnError::TokenAccountMintMismatch.into())
+    );
+}
+
+#[test]
+fn test_extra_metas_in_any_order() {
+    let mut accounts = execute_accounts(&ID, &MINT, true);
+    init_validation_account(&mut accounts[4], &ExecuteMetas::to_extra_account_metas());
+
+    let (counter, _) = Pubkey::find_program_address(&[b"counter", accounts[0].key.as_ref()], &ID);
+    let mut counter_account = TestAccount::owned_by(ID, vec![]);
+    counter_account.key = counter;
+    counter_account.is_writable = true;
+    let mut oracle_account = TestAccount::owned_by(Pubkey::default(), vec![]);
+    oracle_account.key = ORACLE_ID;
+
+    // Passed in the reverse order of the validation account
+    accounts.push(counter_account);
+    accounts.push(oracle_account);
+    assert_eq!(execute(&mut accounts), Ok(()));
+
+    accounts.pop();
+    assert_eq!(
+        execute(&mut accounts),
+        Err(AccountResolutionError::IncorrectAccount.into())
+    );
+}
+
+#[test]
+fn test_extra_meta_privileges() {
+    let mut accounts = execute_accounts(&ID, &MINT, true);
+    init_validation_account(&mut accounts[4], &ExecuteMetas::to_extra_account_metas());
+
+    let (counter, _) = Pubkey::find_program_address(&[b"counter", accounts[0].key.as_ref()], &ID);
+    let mut oracle_account = TestAccount::owned_by(Pubkey::default(), vec![]);
+    oracle_account.key = ORACLE_ID;
+    let mut counter_account = TestAccount::owned_by(ID, vec![]);
+    counter_account.key = counter;
+    accounts.push(oracle_account);
+    accounts.push(counter_account);
+    assert_eq!(
+        execute(&mut accounts),
+        Err(KaptnError::ConstraintWritable.into())
+    );
+
+    accounts[6].is_writable = true;
+    assert_eq!(execute(&mut accounts), Ok(()));
+}
+
+#[test]
+fn test_validation_account_of_other_metas() {
+    let mut accounts = execute_accounts(&ID, &MINT, true);
+    let mut oracle_account = TestAccount::owned_by(Pubkey::default(), vec![]);
+    oracle_account.key = ORACLE_ID;
+    accounts.push(oracle_account);
+
+    // Only the oracle, as if the counter was added after initializing it
+    let oracle = ExtraAccountMeta::new_with_pubkey(&ORACLE_ID, false, false).unwrap();
+    init_validation_account(&mut accounts[4], &[oracle]);
+    assert_eq!(
+        execute(&mut accounts),
+        Err(KaptnError::ExtraMetasMismatch.into())
+    );
+}
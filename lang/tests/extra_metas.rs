@@ -0,0 +1,704 @@
+use core::str::FromStr;
+use kaptn_lang::prelude::*;
+use kaptn_lang::solana_program::{program_option::COption, program_pack::Pack};
+
+mod common;
+use common::*;
+
+const ORACLE_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([9; 32]);
+const AMOUNT: u64 = 100;
+const ORACLE_WRITABLE: bool = true;
+
+#[derive(ExtraMetas)]
+pub struct SeededMetas<'info> {
+    #[meta(seeds = [literal(b"counter"), account_key(source)], writable = true)]
+    pub counter: AccountInfo<'info>,
+    #[meta(seeds = [literal("amount"), amount, ix_data(8, 4)])]
+    pub by_amount: AccountInfo<'info>,
+    #[meta(seeds = [account_data(0, 32, 32), account_key(5)])]
+    pub by_owner: AccountInfo<'info>,
+}
+
+#[derive(ExtraMetas)]
+pub struct WalletMetas<'info> {
+    #[meta(seeds = [literal(b"wallet"), owner_of(source)])]
+    pub source_wallet: AccountInfo<'info>,
+    #[meta(seeds = [owner_of(destination), mint_of(destination)])]
+    pub destination_wallet: AccountInfo<'info>,
+    #[meta(seeds = [delegate_of(source)])]
+    pub delegate: AccountInfo<'info>,
+}
+
+#[derive(ExtraMetas)]
+pub struct ExternalMetas<'info> {
+    #[meta(pubkey = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")]
+    pub token_program: AccountInfo<'info>,
+    #[meta(pubkey = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")]
+    pub associated_token_program: AccountInfo<'info>,
+    #[meta(
+        seeds = [account_key(authority), account_key(token_program), account_key(mint)],
+        program = associated_token_program
+    )]
+    pub authority_ata: AccountInfo<'info>,
+    #[meta(seeds = [literal(b"config")], program = token_program)]
+    pub config: AccountInfo<'info>,
+}
+
+#[derive(ExtraMetas)]
+pub struct PubkeyMetas<'info> {
+    #[meta(pubkey = "So11111111111111111111111111111111111111112")]
+    pub wsol_mint: AccountInfo<'info>,
+    #[meta(pubkey = ORACLE_ID, writable = true)]
+    pub oracle: AccountInfo<'info>,
+}
+
+#[derive(ExtraMetas)]
+pub struct SkippedMetas<'info> {
+    #[meta(skip)]
+    pub label: u64,
+    #[meta(pubkey = ORACLE_ID)]
+    pub oracle: AccountInfo<'info>,
+}
+
+#[derive(ExtraMetas)]
+pub struct TypedMetas<'info> {
+    #[meta(pubkey = ORACLE_ID)]
+    pub token_account: TokenAccount<'info>,
+    #[meta(pubkey = ORACLE_ID)]
+    pub mint_account: MintAccount<'info>,
+}
+
+#[derive(ExtraMetas)]
+pub struct ConstrainedMetas<'info> {
+    #[meta(pubkey = ORACLE_ID, address = ORACLE_ID, owner = spl_token_2022::id())]
+    pub oracle: AccountInfo<'info>,
+    #[meta(
+        seeds = [literal(b"vault")],
+        has_one = mint,
+        constraint = vault.amount > 0
+    )]
+    pub vault: TokenAccount<'info>,
+}
+
+/// `has_one` on an optional meta fails when that meta is not passed.
+#[derive(ExtraMetas)]
+pub struct OptionalOwnerMetas<'info> {
+    #[meta(seeds = [literal(b"vault")], has_one = owner)]
+    pub vault: TokenAccount<'info>,
+    #[meta(pubkey = ORACLE_ID)]
+    pub owner: Option<AccountInfo<'info>>,
+}
+
+#[derive(ExtraMetas)]
+pub struct OptionalMetas<'info> {
+    #[meta(pubkey = ORACLE_ID)]
+    pub oracle: AccountInfo<'info>,
+    #[meta(seeds = [literal(b"vault")], constraint = vault.amount > 0)]
+    pub vault: Option<TokenAccount<'info>>,
+}
+
+#[derive(ExtraMetas)]
+pub struct FlattenedMetas<'info> {
+    #[meta(seeds = [literal(b"counter"), account_key(source)])]
+    pub counter: AccountInfo<'info>,
+    #[meta(flatten)]
+    pub ata: ExternalMetas<'info>,
+    #[meta(seeds = [account_key(counter), account_key(destination)])]
+    pub after: AccountInfo<'info>,
+    #[meta(seeds = [account_key(after)], program = counter)]
+    pub last: AccountInfo<'info>,
+}
+
+#[derive(ExtraMetas)]
+pub struct TupleMetas<'a>(
+    #[meta(pubkey = ORACLE_ID)] pub AccountInfo<'a>,
+    #[meta(skip)] pub u8,
+    #[meta(seeds = [literal(b"vault"), account_key(mint)])] pub TokenAccount<'a>,
+);
+
+#[derive(ExtraMetas)]
+pub struct UnitMetas;
+
+#[derive(ExtraMetas)]
+pub struct GenericMetas<'a, T>
+where
+    T: FromAccountInfo<'a> + AsRef<AccountInfo<'a>>,
+{
+    #[meta(pubkey = ORACLE_ID, owner = spl_token_2022::id())]
+    pub oracle: T,
+    #[meta(flatten)]
+    pub unit: UnitMetas,
+    #[meta(flatten)]
+    pub tuple: TupleMetas<'a>,
+}
+
+#[derive(ExtraMetas)]
+pub struct PrivilegedMetas<'info> {
+    #[meta(seeds = [literal(b"delegate")], signer = true)]
+    pub delegate: AccountInfo<'info>,
+    #[meta(pubkey = ORACLE_ID, writable = ORACLE_WRITABLE)]
+    pub oracle: AccountInfo<'info>,
+    #[meta(pubkey = ORACLE_ID, writable = !ORACLE_WRITABLE)]
+    pub oracle_readonly: AccountInfo<'info>,
+}
+
+/// Sets the keys of the accounts after the base accounts to the addresses
+/// `metas` resolve to.
+fn resolve_keys(metas: &[ExtraAccountMeta], accounts: &mut [TestAccount]) {
+    let instruction_data = TransferHookInstruction::Execute { amount: AMOUNT }.pack();
+    let passed = accounts.len().saturating_sub(5);
+    for (i, meta) in metas.iter().enumerate().take(passed) {
+        let resolved = meta
+            .resolve(&instruction_data, &PROGRAM_ID, |index| {
+                accounts
+                    .get(index)
+                    .map(|account| (&account.key, Some(account.data.as_slice())))
+            })
+            .unwrap();
+        accounts[5 + i].key = resolved.pubkey;
+    }
+}
+
+fn seeds_of(meta: &ExtraAccountMeta) -> Vec<Seed> {
+    Seed::unpack_address_config(&meta.address_config).unwrap()
+}
+
+#[test]
+fn test_seed_dsl() {
+    let metas = SeededMetas::to_extra_account_metas();
+    assert_eq!(metas.len(), 3);
+    // `account_key(5)` stays absolute, so the struct cannot be flattened
+    const { assert!(!SeededMetas::FLATTENABLE) };
+    const { assert!(ExternalMetas::FLATTENABLE) };
+
+    assert_eq!(
+        seeds_of(&metas[0]),
+        vec![
+            Seed::Literal {
+                bytes: b"counter".to_vec()
+            },
+            Seed::AccountKey { index: 0 },
+        ]
+    );
+    assert!(bool::from(metas[0].is_writable));
+    assert!(!bool::from(metas[0].is_signer));
+
+    assert_eq!(
+        seeds_of(&metas[1]),
+        vec![
+            Seed::Literal {
+                bytes: b"amount".to_vec()
+            },
+            Seed::InstructionData {
+                index: 8,
+                length: 8
+            },
+            Seed::InstructionData {
+                index: 8,
+                length: 4
+            },
+        ]
+    );
+
+    assert_eq!(
+        seeds_of(&metas[2]),
+        vec![
+            Seed::AccountData {
+                account_index: 0,
+                data_index: 32,
+                length: 32
+            },
+            Seed::AccountKey { index: 5 },
+        ]
+    );
+}
+
+#[test]
+fn test_internal_and_external_pdas() {
+    let metas = SeededMetas::to_extra_account_metas();
+    assert!(metas.iter().all(|meta| meta.discriminator == 1));
+
+    let metas = ExternalMetas::to_extra_account_metas();
+    assert_eq!(metas[0].discriminator, 0);
+    assert_eq!(metas[1].discriminator, 0);
+    assert_eq!(metas[2].discriminator, 128 + 6);
+    assert_eq!(
+        seeds_of(&metas[2]),
+        vec![
+            Seed::AccountKey { index: 3 },
+            Seed::AccountKey { index: 5 },
+            Seed::AccountKey { index: 1 },
+        ]
+    );
+    assert_eq!(metas[3].discriminator, 128 + 5);
+}
+
+#[test]
+fn test_pubkey_metas() {
+    let metas = PubkeyMetas::to_extra_account_metas();
+    let wsol = Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap();
+    assert_eq!(
+        metas[0],
+        ExtraAccountMeta::new_with_pubkey(&wsol, false, false).unwrap()
+    );
+    assert_eq!(
+        metas[1],
+        ExtraAccountMeta::new_with_pubkey(&ORACLE_ID, false, true).unwrap()
+    );
+}
+
+#[test]
+fn test_skipped_field() {
+    assert_eq!(SkippedMetas::to_extra_account_metas().len(), 1);
+
+    let mut accounts = (0..6).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    resolve_keys(&SkippedMetas::to_extra_account_metas(), &mut accounts);
+    let accounts = account_infos(&mut accounts);
+
+    let metas = SkippedMetas::from_accounts(&PROGRAM_ID, &accounts, AMOUNT).unwrap();
+    assert_eq!(metas.label, 0);
+    assert_eq!(*metas.oracle.key, ORACLE_ID);
+}
+
+#[test]
+fn test_typed_accounts() {
+    let mint = Pubkey::new_unique();
+    let wallet = Pubkey::new_unique();
+    let mut accounts = (0..5).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    accounts.push(TestAccount::new(token_account_data(new_token_account(
+        &mint, &wallet, 42,
+    ))));
+    accounts.push(TestAccount::new(mint_data(new_mint(1_000, 6))));
+    resolve_keys(&TypedMetas::to_extra_account_metas(), &mut accounts);
+
+    let metas =
+        TypedMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).unwrap();
+    assert_eq!(metas.token_account.owner, wallet);
+    assert_eq!(metas.token_account.amount, 42);
+    assert_eq!(metas.mint_account.supply, 1_000);
+    assert_eq!(metas.mint_account.decimals, 6);
+
+    // Token accounts must be owned by Token-2022
+    accounts[5].owner = Pubkey::new_unique();
+    assert_eq!(
+        TypedMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).err(),
+        Some(ProgramError::IllegalOwner)
+    );
+
+    // and must be initialized
+    accounts[5].owner = spl_token_2022::id();
+    accounts[5].data = vec![0; Account::LEN];
+    assert_eq!(
+        TypedMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).err(),
+        Some(ProgramError::UninitializedAccount)
+    );
+}
+
+#[test]
+fn test_constraints() {
+    let mint = Pubkey::new_unique();
+    let wallet = Pubkey::new_unique();
+    let mut accounts = (0..5).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    accounts[1].key = mint;
+    accounts.push(TestAccount::new(vec![]));
+    accounts.push(TestAccount::new(token_account_data(new_token_account(
+        &mint, &wallet, 10,
+    ))));
+    resolve_keys(&ConstrainedMetas::to_extra_account_metas(), &mut accounts);
+    assert!(
+        ConstrainedMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).is_ok()
+    );
+
+    let check = |accounts: &mut [TestAccount], error: KaptnError| {
+        assert_eq!(
+            ConstrainedMetas::from_accounts(&PROGRAM_ID, &account_infos(accounts), AMOUNT).err(),
+            Some(error.into())
+        );
+    };
+
+    accounts[5].owner = Pubkey::new_unique();
+    check(&mut accounts, KaptnError::ConstraintOwner);
+    accounts[5].owner = spl_token_2022::id();
+
+    accounts[6].data = token_account_data(new_token_account(&Pubkey::new_unique(), &wallet, 10));
+    check(&mut accounts, KaptnError::ConstraintHasOne);
+
+    accounts[6].data = token_account_data(new_token_account(&mint, &wallet, 0));
+    check(&mut accounts, KaptnError::ConstraintRaw);
+}
+
+#[test]
+fn test_has_one_optional_meta() {
+    let mint = Pubkey::new_unique();
+    let mut accounts = (0..7).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    accounts[5].data = token_account_data(new_token_account(&mint, &ORACLE_ID, 10));
+    resolve_keys(&OptionalOwnerMetas::to_extra_account_metas(), &mut accounts);
+    let from_accounts = |accounts: &mut [TestAccount]| {
+        OptionalOwnerMetas::from_accounts(&PROGRAM_ID, &account_infos(accounts), AMOUNT).err()
+    };
+    assert_eq!(from_accounts(&mut accounts), None);
+
+    accounts[5].data = token_account_data(new_token_account(&mint, &Pubkey::new_unique(), 10));
+    assert_eq!(
+        from_accounts(&mut accounts),
+        Some(KaptnError::ConstraintHasOne.into())
+    );
+
+    // Without the owner the relation cannot hold
+    accounts[5].data = token_account_data(new_token_account(&mint, &ORACLE_ID, 10));
+    accounts.truncate(6);
+    assert_eq!(
+        from_accounts(&mut accounts),
+        Some(KaptnError::ConstraintHasOne.into())
+    );
+}
+
+#[test]
+fn test_optional_metas() {
+    let mint = Pubkey::new_unique();
+    let mut accounts = (0..6).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    resolve_keys(&OptionalMetas::to_extra_account_metas(), &mut accounts);
+    let metas =
+        OptionalMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).unwrap();
+    assert!(metas.vault.is_none());
+
+    accounts.push(TestAccount::new(token_account_data(new_token_account(
+        &mint, &mint, 5,
+    ))));
+    resolve_keys(&OptionalMetas::to_extra_account_metas(), &mut accounts);
+    let metas =
+        OptionalMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).unwrap();
+    assert_eq!(metas.vault.unwrap().amount, 5);
+
+    // Constraints still apply when the optional meta is passed
+    accounts[6].data = token_account_data(new_token_account(&mint, &mint, 0));
+    assert_eq!(
+        OptionalMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).err(),
+        Some(KaptnError::ConstraintRaw.into())
+    );
+
+    // Required metas must be passed
+    accounts.truncate(5);
+    assert_eq!(
+        OptionalMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+}
+
+#[test]
+fn test_remaining_accounts_after_optional_meta() {
+    let mut accounts = (0..8).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    resolve_keys(
+        &OptionalMetas::to_extra_account_metas()[..1],
+        &mut accounts[..6],
+    );
+    let infos = account_infos(&mut accounts);
+
+    // The vault is left out, so the accounts after the oracle are remaining
+    let mut resolver = MetaResolver::new(&PROGRAM_ID, &infos, AMOUNT).unwrap();
+    let metas = OptionalMetas::from_resolver(&mut resolver).unwrap();
+    assert!(metas.vault.is_none());
+    let remaining = resolver.remaining_accounts();
+    assert_eq!(remaining.len(), 2);
+    assert_eq!(remaining[0].key, infos[6].key);
+}
+
+#[test]
+fn test_flattened_metas() {
+    assert_eq!(FlattenedMetas::LEN, 7);
+    let metas = FlattenedMetas::to_extra_account_metas();
+    assert_eq!(metas.len(), 7);
+
+    // The flattened metas refer to their own fields at their new position
+    assert_eq!(metas[3].discriminator, 128 + 7);
+    assert_eq!(
+        seeds_of(&metas[3]),
+        vec![
+            Seed::AccountKey { index: 3 },
+            Seed::AccountKey { index: 6 },
+            Seed::AccountKey { index: 1 },
+        ]
+    );
+    assert_eq!(metas[4].discriminator, 128 + 6);
+
+    // Fields after the flattened struct are shifted past its metas
+    assert_eq!(
+        seeds_of(&metas[5]),
+        vec![Seed::AccountKey { index: 5 }, Seed::AccountKey { index: 2 }]
+    );
+    assert_eq!(metas[6].discriminator, 128 + 5);
+    assert_eq!(seeds_of(&metas[6]), vec![Seed::AccountKey { index: 10 }]);
+
+    let mut accounts = (0..12)
+        .map(|_| TestAccount::new(vec![]))
+        .collect::<Vec<_>>();
+    resolve_keys(&FlattenedMetas::to_extra_account_metas(), &mut accounts);
+    let keys = accounts
+        .iter()
+        .map(|account| account.key)
+        .collect::<Vec<_>>();
+    let metas =
+        FlattenedMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).unwrap();
+    assert_eq!(*metas.counter.key, keys[5]);
+    assert_eq!(*metas.ata.token_program.key, keys[6]);
+    assert_eq!(*metas.ata.config.key, keys[9]);
+    assert_eq!(*metas.after.key, keys[10]);
+    assert_eq!(*metas.last.key, keys[11]);
+
+    accounts.truncate(11);
+    assert_eq!(
+        FlattenedMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+}
+
+#[test]
+fn test_metas_bound_by_address() {
+    let mut accounts = (0..8).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    accounts[0].data = token_account_data(new_token_account(
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        1,
+    ));
+    resolve_keys(&SeededMetas::to_extra_account_metas(), &mut accounts);
+    accounts[5].is_writable = true;
+    let keys = accounts
+        .iter()
+        .map(|account| account.key)
+        .collect::<Vec<_>>();
+
+    // Extra metas can be passed in any order
+    accounts[5..].reverse();
+    let metas =
+        SeededMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).unwrap();
+    assert_eq!(*metas.counter.key, keys[5]);
+    assert_eq!(*metas.by_amount.key, keys[6]);
+    assert_eq!(*metas.by_owner.key, keys[7]);
+
+    // Seeds depend on the amount
+    assert_eq!(
+        SeededMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT + 1).err(),
+        Some(AccountResolutionError::IncorrectAccount.into())
+    );
+
+    // An account that is not the resolved address is rejected
+    accounts[5].key = Pubkey::new_unique();
+    assert_eq!(
+        SeededMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).err(),
+        Some(AccountResolutionError::IncorrectAccount.into())
+    );
+
+    // and so is a list that leaves a required meta out
+    accounts[5].key = keys[7];
+    accounts[5..].reverse();
+    accounts.truncate(7);
+    assert_eq!(
+        SeededMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+}
+
+#[test]
+fn test_struct_shapes() {
+    assert_eq!(UnitMetas::LEN, 0);
+    assert!(UnitMetas::to_extra_account_metas().is_empty());
+
+    let metas = TupleMetas::to_extra_account_metas();
+    assert_eq!(metas.len(), 2);
+    assert_eq!(
+        seeds_of(&metas[1]),
+        vec![
+            Seed::Literal {
+                bytes: b"vault".to_vec()
+            },
+            Seed::AccountKey { index: 1 },
+        ]
+    );
+
+    // The flattened tuple struct follows the generic field
+    let metas = GenericMetas::<AccountInfo>::to_extra_account_metas();
+    assert_eq!(metas.len(), 3);
+    assert_eq!(
+        metas[2].address_config,
+        TupleMetas::to_extra_account_metas()[1].address_config
+    );
+
+    let mint = Pubkey::new_unique();
+    let mut accounts = (0..5).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    accounts.push(TestAccount::new(token_account_data(new_token_account(
+        &mint, &mint, 3,
+    ))));
+    accounts.push(TestAccount::new(vec![]));
+    accounts.push(TestAccount::new(token_account_data(new_token_account(
+        &mint, &mint, 7,
+    ))));
+    resolve_keys(&metas, &mut accounts);
+
+    let metas = GenericMetas::<TokenAccount>::from_accounts(
+        &PROGRAM_ID,
+        &account_infos(&mut accounts),
+        AMOUNT,
+    )
+    .unwrap();
+    assert_eq!(metas.oracle.amount, 3);
+    assert_eq!(metas.tuple.1, 0);
+    assert_eq!(metas.tuple.2.amount, 7);
+
+    accounts[5].owner = Pubkey::new_unique();
+    assert_eq!(
+        GenericMetas::<AccountInfo>::from_accounts(
+            &PROGRAM_ID,
+            &account_infos(&mut accounts),
+            AMOUNT
+        )
+        .err(),
+        Some(KaptnError::ConstraintOwner.into())
+    );
+}
+
+#[test]
+fn test_privileges() {
+    let metas = PrivilegedMetas::to_extra_account_metas();
+    assert!(bool::from(metas[0].is_signer));
+    assert!(bool::from(metas[1].is_writable));
+    assert!(!bool::from(metas[2].is_writable));
+
+    let mut accounts = (0..8).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    resolve_keys(&metas, &mut accounts);
+    accounts[5].is_signer = true;
+    accounts[6].is_writable = true;
+    assert!(
+        PrivilegedMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).is_ok()
+    );
+
+    accounts[5].is_signer = false;
+    assert_eq!(
+        PrivilegedMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).err(),
+        Some(KaptnError::ConstraintSigner.into())
+    );
+    accounts[5].is_signer = true;
+
+    accounts[6].is_writable = false;
+    assert_eq!(
+        PrivilegedMetas::from_accounts(&PROGRAM_ID, &account_infos(&mut accounts), AMOUNT).err(),
+        Some(KaptnError::ConstraintWritable.into())
+    );
+}
+
+#[test]
+fn test_describe() {
+    use kaptn_lang::metadata::{ExtraMetaKind, SeedDescription};
+
+    let metas = FlattenedMetas::describe();
+    let names = metas
+        .iter()
+        .map(|meta| meta.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "counter",
+            "ata.token_program",
+            "ata.associated_token_program",
+            "ata.authority_ata",
+            "ata.config",
+            "after",
+            "last"
+        ]
+    );
+    assert_eq!(
+        metas[3].kind,
+        ExtraMetaKind::Pda {
+            program: Some("ata.associated_token_program".to_string()),
+            seeds: vec![
+                SeedDescription::AccountKey("authority".to_string()),
+                SeedDescription::AccountKey("ata.token_program".to_string()),
+                SeedDescription::AccountKey("mint".to_string()),
+            ],
+        }
+    );
+    assert_eq!(
+        metas[6].kind,
+        ExtraMetaKind::Pda {
+            program: Some("counter".to_string()),
+            seeds: vec![SeedDescription::AccountKey("after".to_string())],
+        }
+    );
+
+    assert!(OptionalMetas::describe()[1].optional);
+    assert!(PrivilegedMetas::describe()[0].signer);
+}
+
+#[test]
+fn test_json() {
+    assert_eq!(
+        PubkeyMetas::to_json(),
+        format!(
+            "[{{\"name\":\"wsol_mint\",\"pubkey\":\"So11111111111111111111111111111111111111112\",\
+             \"signer\":false,\"writable\":false,\"optional\":false}},\
+             {{\"name\":\"oracle\",\"pubkey\":\"{}\",\"signer\":false,\"writable\":true,\"optional\":false}}]",
+            ORACLE_ID
+        )
+    );
+    assert_eq!(
+        OptionalMetas::to_json(),
+        format!(
+            "[{{\"name\":\"oracle\",\"pubkey\":\"{}\",\"signer\":false,\"writable\":false,\"optional\":false}},\
+             {{\"name\":\"vault\",\"pda\":{{\"program\":null,\"seeds\":[{{\"literal\":[118,97,117,108,116]}}]}},\
+             \"signer\":false,\"writable\":false,\"optional\":true}}]",
+            ORACLE_ID
+        )
+    );
+}
+
+#[test]
+fn test_token_account_seeds() {
+    let metas = WalletMetas::to_extra_account_metas();
+    assert_eq!(
+        seeds_of(&metas[0])[1],
+        Seed::AccountData {
+            account_index: 0,
+            data_index: 32,
+            length: 32
+        }
+    );
+    assert_eq!(
+        seeds_of(&metas[1]),
+        vec![
+            Seed::AccountData {
+                account_index: 2,
+                data_index: 32,
+                length: 32
+            },
+            Seed::AccountData {
+                account_index: 2,
+                data_index: 0,
+                length: 32
+            },
+        ]
+    );
+
+    // The seeds read the owner and delegate of the source token account
+    let (mint, owner, delegate) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut accounts = (0..8).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+    accounts[0].data = token_account_data(Account {
+        delegate: COption::Some(delegate),
+        ..new_token_account(&mint, &owner, 0)
+    });
+    accounts[2].data = token_account_data(new_token_account(&mint, &owner, 0));
+    resolve_keys(&metas, &mut accounts);
+
+    let (source_wallet, _) =
+        Pubkey::find_program_address(&[b"wallet", owner.as_ref()], &PROGRAM_ID);
+    let (destination_wallet, _) =
+        Pubkey::find_program_address(&[owner.as_ref(), mint.as_ref()], &PROGRAM_ID);
+    let (delegate_pda, _) = Pubkey::find_program_address(&[delegate.as_ref()], &PROGRAM_ID);
+    assert_eq!(accounts[5].key, source_wallet);
+    assert_eq!(accounts[6].key, destination_wallet);
+    assert_eq!(accounts[7].key, delegate_pda);
+}
@@ -0,0 +1,156 @@
+use kaptn_lang::prelude::*;
+
+mod common;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+declare_mint!("A7yUYJNEVYRLE4QWsnc9rE9JRsm7DfqEmLscQVwkffAk");
+
+const ADMIN: Pubkey = Pubkey::new_from_array([1; 32]);
+#[transfer_hook(instructions(set_limit, admin::ping))]
+pub fn hook(_ctx: TransferContext<()>) -> ProgramResult {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetLimit<'info> {
+    #[accounts(signer, address = ADMIN)]
+    pub admin: AccountInfo<'info>,
+    #[accounts(writable, owner = crate::ID)]
+    pub config: AccountInfo<'info>,
+}
+
+#[instruction]
+pub fn set_limit(ctx: InstructionContext<SetLimit>, limit: u64, label: String) -> ProgramResult {
+    require_eq!(label, "daily", ProgramError::InvalidArgument);
+    ctx.accounts.config.try_borrow_mut_data()?[..8].copy_from_slice(&limit.to_le_bytes());
+    Ok(())
+}
+
+mod admin {
+    use kaptn_lang::prelude::*;
+
+    #[instruction]
+    pub fn ping(ctx: InstructionContext<()>) -> ProgramResult {
+        require_eq!(
+            ctx.remaining_accounts.len(),
+            1,
+            ProgramError::NotEnoughAccountKeys
+        );
+        Ok(())
+    }
+}
+
+/// The admin signing and a writable config account owned by the program.
+fn set_limit_accounts() -> Vec<TestAccount> {
+    let mut admin = TestAccount::owned_by(Pubkey::default(), vec![]);
+    admin.key = ADMIN;
+    admin.is_signer = true;
+    let mut config = TestAccount::owned_by(ID, vec![0; 8]);
+    config.is_writable = true;
+    vec![admin, config]
+}
+
+#[test]
+fn test_instruction_data() {
+    let hash = kaptn_lang::solana_program::hash::hash(b"instruction:set_limit");
+    assert_eq!(set_limit::DISCRIMINATOR, hash.to_bytes()[..8]);
+    assert_eq!(set_limit::NAME, "set_limit");
+    assert_eq!(admin::ping::NAME, "ping");
+
+    let data = set_limit::data(500, "daily".to_string());
+    assert_eq!(data[..8], set_limit::DISCRIMINATOR);
+    assert_eq!(data[8..16], 500u64.to_le_bytes());
+    assert_eq!(data[16..20], 5u32.to_le_bytes());
+    assert_eq!(&data[20..], b"daily");
+    assert_eq!(admin::ping::data(), admin::ping::DISCRIMINATOR);
+}
+
+#[test]
+fn test_dispatch() {
+    let mut accounts = set_limit_accounts();
+    let infos = account_infos(&mut accounts);
+    let data = set_limit::data(500, "daily".to_string());
+    assert_eq!(__process_instruction(&ID, &infos, &data), Ok(()));
+    assert_eq!(
+        infos[1].try_borrow_data().unwrap()[..8],
+        500u64.to_le_bytes()
+    );
+
+    let data = set_limit::data(500, "weekly".to_string());
+    assert_eq!(
+        __process_instruction(&ID, &infos, &data),
+        Err(ProgramError::InvalidArgument)
+    );
+    let mut data = set_limit::data(500, "daily".to_string());
+    data.push(0);
+    assert_eq!(
+        __process_instruction(&ID, &infos, &data),
+        Err(ProgramError::InvalidInstructionData)
+    );
+
+    assert_eq!(
+        __process_instruction(&ID, &infos, &admin::ping::data()),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    assert_eq!(
+        __process_instruction(&ID, &infos[..1], &admin::ping::data()),
+        Ok(())
+    );
+
+    // Anything else falls back to the Transfer Hook interface
+    assert_eq!(
+        __process_instruction(&ID, &infos, &[0; 8]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
+
+#[test]
+fn test_accounts_checks() {
+    let mut accounts = set_limit_accounts();
+    assert!(SetLimit::try_accounts(&mut account_infos(&mut accounts).as_slice()).is_ok());
+
+    let mut remaining = &account_infos(&mut accounts)[..1];
+    assert_eq!(
+        SetLimit::try_accounts(&mut remaining).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+
+    accounts[0].is_signer = false;
+    assert_eq!(
+        SetLimit::try_accounts(&mut account_infos(&mut accounts).as_slice()).err(),
+        Some(KaptnError::ConstraintSigner.into())
+    );
+    accounts[0].is_signer = true;
+
+    accounts[0].key = Pubkey::new_unique();
+    assert_eq!(
+        SetLimit::try_accounts(&mut account_infos(&mut accounts).as_slice()).err(),
+        Some(KaptnError::ConstraintAddress.into())
+    );
+    accounts[0].key = ADMIN;
+
+    accounts[1].is_writable = false;
+    assert_eq!(
+        SetLimit::try_accounts(&mut account_infos(&mut accounts).as_slice()).err(),
+        Some(KaptnError::ConstraintWritable.into())
+    );
+    accounts[1].is_writable = true;
+
+    accounts[1].owner = Pubkey::new_unique();
+    assert_eq!(
+        SetLimit::try_accounts(&mut account_infos(&mut accounts).as_slice()).err(),
+        Some(KaptnError::ConstraintOwner.into())
+    );
+}
+
+#[test]
+fn test_remaining_accounts() {
+    let mut accounts = set_limit_accounts();
+    accounts.push(TestAccount::owned_by(ID, vec![]));
+    let infos = account_infos(&mut accounts);
+    let ctx = InstructionContext::<SetLimit>::new(&ID, &infos).unwrap();
+    assert_eq!(ctx.accounts.admin.key, &ADMIN);
+    assert_eq!(ctx.remaining_accounts.len(), 1);
+    assert_eq!(ctx.remaining_accounts[0].key, infos[2].key);
+}
@@ -0,0 +1,42 @@
+use kaptn_lang::prelude::*;
+
+mod common;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+declare_mint!("A7yUYJNEVYRLE4QWsnc9rE9JRsm7DfqEmLscQVwkffAk");
+
+const OTHER_MINT: Pubkey = Pubkey::new_from_array([2; 32]);
+
+fn accepted_mint(mint: &Pubkey) -> bool {
+    *mint == MINT || *mint == OTHER_MINT
+}
+
+#[transfer_hook(mint_check = accepted_mint)]
+pub fn hook(_ctx: TransferContext<()>) -> ProgramResult {
+    Ok(())
+}
+
+#[test]
+fn test_execute_mint_check() {
+    let execute = TransferHookInstruction::Execute { amount: 0 }.pack();
+    let accounts_for = |mint| {
+        let mut accounts = (0..5).map(|_| TestAccount::new(vec![])).collect::<Vec<_>>();
+        accounts[1].key = mint;
+        accounts
+    };
+
+    let mut accounts = accounts_for(Pubkey::new_unique());
+    assert_eq!(
+        __process_instruction(&ID, &account_infos(&mut accounts), &execute),
+        Err(KaptnError::InvalidMint.into())
+    );
+
+    // Accepted mints get past the check and fail on the empty token accounts
+    for mint in [MINT, OTHER_MINT] {
+        let mut accounts = accounts_for(mint);
+        let result = __process_instruction(&ID, &account_infos(&mut accounts), &execute);
+        assert!(result.is_err());
+        assert_ne!(result, Err(KaptnError::InvalidMint.into()));
+    }
+}
@@ -0,0 +1,69 @@
+use kaptn_lang::prelude::*;
+
+#[kaptn_error]
+pub enum PolicyError {
+    #[msg("Amount out of range")]
+    AmountOutOfRange,
+    #[msg("Wrong owner")]
+    WrongOwner,
+}
+
+fn check_range(amount: u64) -> ProgramResult {
+    require_gte!(amount, 10, PolicyError::AmountOutOfRange);
+    require_lte!(amount, 1_000, PolicyError::AmountOutOfRange);
+    require_neq!(amount, 500, ProgramError::InvalidArgument);
+    Ok(())
+}
+
+fn check_owner(owner: &Pubkey, expected: Pubkey) -> ProgramResult {
+    require_keys_eq!(*owner, expected, PolicyError::WrongOwner);
+    require_keys_neq!(*owner, Pubkey::default(), ProgramError::InvalidAccountData);
+    Ok(())
+}
+
+#[test]
+fn test_require() {
+    let check = |flag: bool| -> ProgramResult {
+        require!(flag, ProgramError::InvalidArgument);
+        Ok(())
+    };
+    assert_eq!(check(true), Ok(()));
+    assert_eq!(check(false), Err(ProgramError::InvalidArgument));
+}
+
+#[test]
+fn test_require_cmp() {
+    assert_eq!(check_range(10), Ok(()));
+    assert_eq!(check_range(1_000), Ok(()));
+    assert_eq!(check_range(9), Err(PolicyError::AmountOutOfRange.into()));
+    assert_eq!(
+        check_range(1_001),
+        Err(PolicyError::AmountOutOfRange.into())
+    );
+    assert_eq!(check_range(500), Err(ProgramError::InvalidArgument));
+
+    let check = |left: u8, right: u8| -> Result<(), PolicyError> {
+        require_eq!(left, right, PolicyError::AmountOutOfRange);
+        require_gt!(left, 1, PolicyError::AmountOutOfRange);
+        require_lt!(left, 5, PolicyError::AmountOutOfRange);
+        Ok(())
+    };
+    assert_eq!(check(2, 2), Ok(()));
+    assert_eq!(check(2, 3), Err(PolicyError::AmountOutOfRange));
+    assert_eq!(check(1, 1), Err(PolicyError::AmountOutOfRange));
+    assert_eq!(check(5, 5), Err(PolicyError::AmountOutOfRange));
+}
+
+#[test]
+fn test_require_keys() {
+    let owner = Pubkey::new_unique();
+    assert_eq!(check_owner(&owner, owner), Ok(()));
+    assert_eq!(
+        check_owner(&owner, Pubkey::new_unique()),
+        Err(PolicyError::WrongOwner.into())
+    );
+    assert_eq!(
+        check_owner(&Pubkey::default(), Pubkey::default()),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
@@ -0,0 +1,231 @@
+use kaptn_lang::prelude::*;
+
+mod common;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const OTHER_PROGRAM: Pubkey = Pubkey::new_from_array([3; 32]);
+
+#[account]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub max_amount: Option<u64>,
+    #[max_len(4)]
+    pub allowlist: Vec<Pubkey>,
+    #[max_len(16)]
+    pub label: String,
+    pub counters: [u16; 3],
+}
+
+#[account(owner = OTHER_PROGRAM)]
+#[derive(Debug, PartialEq)]
+pub struct Counter(pub u64);
+
+#[account(zero_copy)]
+#[derive(Debug, PartialEq)]
+pub struct Volume {
+    pub total: u64,
+    pub by_hour: [u64; 24],
+    pub last_sender: Pubkey,
+}
+
+/// Derives `Clone` and `Copy` itself, the attribute only adds what is missing.
+#[account(zero_copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tally {
+    pub count: u64,
+}
+
+#[derive(ExtraMetas)]
+pub struct StateMetas<'info> {
+    #[meta(seeds = [literal(b"config")], writable = true)]
+    pub config: StateAccount<'info, Config>,
+}
+
+#[derive(ExtraMetas)]
+pub struct ZeroCopyMetas<'info> {
+    #[meta(seeds = [literal(b"volume")], writable = true)]
+    pub volume: ZeroCopyAccount<'info, Volume>,
+}
+
+fn config() -> Config {
+    Config {
+        admin: Pubkey::new_from_array([1; 32]),
+        max_amount: Some(1_000),
+        allowlist: vec![Pubkey::new_from_array([2; 32])],
+        label: "ahoy".to_string(),
+        counters: [1, 2, 3],
+    }
+}
+
+fn config_data(config: &Config) -> Vec<u8> {
+    let mut data = vec![0; Config::LEN];
+    config.try_to_data(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn test_space() {
+    assert_eq!(Config::INIT_SPACE, 32 + 9 + (4 + 4 * 32) + (4 + 16) + 6);
+    assert_eq!(Config::LEN, 8 + Config::INIT_SPACE);
+    assert_eq!(Counter::LEN, 16);
+}
+
+#[test]
+fn test_discriminator_and_owner() {
+    let hash = kaptn_lang::solana_program::hash::hash(b"account:Config");
+    assert_eq!(Config::DISCRIMINATOR, hash.to_bytes()[..8]);
+    assert_eq!(Config::OWNER, ID);
+    assert_eq!(Counter::OWNER, OTHER_PROGRAM);
+}
+
+#[test]
+fn test_data() {
+    let data = config_data(&config());
+    assert_eq!(data[..8], Config::DISCRIMINATOR);
+    assert_eq!(Config::try_from_data(&data), Ok(config()));
+
+    let mut largest = config();
+    largest.allowlist = vec![Pubkey::new_unique(); 4];
+    largest.label = "x".repeat(16);
+    assert_eq!(Config::try_from_data(&config_data(&largest)), Ok(largest));
+
+    assert_eq!(
+        Counter::try_from_data(&data),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        Config::try_from_data(&data[..20]),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        config().try_to_data(&mut [0; 20]),
+        Err(ProgramError::AccountDataTooSmall)
+    );
+}
+
+#[test]
+fn test_state_account() {
+    let (config_key, _) = Pubkey::find_program_address(&[b"config"], &ID);
+    let mut accounts = (0..5)
+        .map(|_| TestAccount::owned_by(Pubkey::default(), vec![]))
+        .collect::<Vec<_>>();
+    let mut config_account = TestAccount::owned_by(ID, config_data(&config()));
+    config_account.key = config_key;
+    config_account.is_writable = true;
+    accounts.push(config_account);
+
+    {
+        let infos = account_infos(&mut accounts);
+        let mut metas = StateMetas::from_accounts(&ID, &infos, 100).unwrap();
+        assert_eq!(*metas.config, config());
+        assert_eq!(metas.config.key(), &config_key);
+
+        metas.config.counters[0] += 1;
+        metas.config.save().unwrap();
+    }
+    assert_eq!(
+        Config::try_from_data(&accounts[5].data).unwrap().counters,
+        [2, 2, 3]
+    );
+
+    accounts[5].owner = OTHER_PROGRAM;
+    assert_eq!(
+        StateMetas::from_accounts(&ID, &account_infos(&mut accounts), 100).err(),
+        Some(ProgramError::IllegalOwner)
+    );
+
+    accounts[5].owner = ID;
+    accounts[5].data[0] ^= 1;
+    assert_eq!(
+        StateMetas::from_accounts(&ID, &account_infos(&mut accounts), 100).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+}
+
+fn volume_data() -> Vec<u8> {
+    let mut data = vec![0; Volume::LEN];
+    let volume = Volume::init_data(&mut data).unwrap();
+    volume.total = 10;
+    data
+}
+
+#[test]
+fn test_zero_copy_data() {
+    assert_eq!(Volume::LEN, 8 + 8 + 24 * 8 + 32);
+    assert_eq!(Volume::OWNER, ID);
+    assert_eq!(Tally::LEN, 8 + 8);
+
+    let mut data = volume_data();
+    assert_eq!(data[..8], Volume::DISCRIMINATOR);
+    assert_eq!(Volume::from_data(&data).unwrap().total, 10);
+    Volume::from_data_mut(&mut data).unwrap().by_hour[3] = 5;
+    assert_eq!(data[8 + 8 + 3 * 8], 5);
+
+    assert_eq!(
+        Volume::init_data(&mut data).err(),
+        Some(ProgramError::AccountAlreadyInitialized)
+    );
+    assert_eq!(
+        Volume::from_data(&data[..Volume::LEN - 1]).err(),
+        Some(ProgramError::AccountDataTooSmall)
+    );
+    assert_eq!(
+        Volume::from_data(&config_data(&config())).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+
+    // The struct must be aligned in the account data
+    let mut shifted = vec![0; Volume::LEN + 1];
+    shifted[1..].copy_from_slice(&data);
+    assert_eq!(
+        Volume::from_data(&shifted[1..]).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn test_zero_copy_account() {
+    let (volume_key, _) = Pubkey::find_program_address(&[b"volume"], &ID);
+    let mut accounts = (0..5)
+        .map(|_| TestAccount::owned_by(Pubkey::default(), vec![]))
+        .collect::<Vec<_>>();
+    let mut volume_account = TestAccount::owned_by(ID, volume_data());
+    volume_account.key = volume_key;
+    volume_account.is_writable = true;
+    accounts.push(volume_account);
+
+    {
+        let infos = account_infos(&mut accounts);
+        let metas = ZeroCopyMetas::from_accounts(&ID, &infos, 100).unwrap();
+        assert_eq!(metas.volume.key(), &volume_key);
+        assert_eq!(metas.volume.load().unwrap().total, 10);
+
+        {
+            let mut volume = metas.volume.load_mut().unwrap();
+            volume.total += 100;
+            volume.last_sender = Pubkey::new_from_array([4; 32]);
+            assert_eq!(
+                metas.volume.load().err(),
+                Some(ProgramError::AccountBorrowFailed)
+            );
+        }
+        assert_eq!(metas.volume.load().unwrap().total, 110);
+    }
+    assert_eq!(Volume::from_data(&accounts[5].data).unwrap().total, 110);
+
+    accounts[5].owner = OTHER_PROGRAM;
+    assert_eq!(
+        ZeroCopyMetas::from_accounts(&ID, &account_infos(&mut accounts), 100).err(),
+        Some(ProgramError::IllegalOwner)
+    );
+
+    accounts[5].owner = ID;
+    accounts[5].data.truncate(20);
+    assert_eq!(
+        ZeroCopyMetas::from_accounts(&ID, &account_infos(&mut accounts), 100).err(),
+        Some(ProgramError::AccountDataTooSmall)
+    );
+}
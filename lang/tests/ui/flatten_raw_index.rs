@@ -0,0 +1,19 @@
+use kaptn_lang::prelude::*;
+
+#[derive(ExtraMetas)]
+pub struct VaultMetas<'info> {
+    #[meta(pubkey = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")]
+    pub token_program: AccountInfo<'info>,
+    #[meta(seeds = [literal(b"vault"), account_key(5)])]
+    pub vault: AccountInfo<'info>,
+}
+
+#[derive(ExtraMetas)]
+pub struct HookMetas<'info> {
+    #[meta(seeds = [literal(b"counter")])]
+    pub counter: AccountInfo<'info>,
+    #[meta(flatten)]
+    pub vault: VaultMetas<'info>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use kaptn_lang::prelude::*;
+
+#[derive(ExtraMetas)]
+pub struct VaultMetas<'info> {
+    #[meta(seeds = [literal(b"vault")])]
+    pub vault: AccountInfo<'info>,
+}
+
+#[derive(ExtraMetas)]
+pub struct HookMetas<'info> {
+    #[meta(flatten = true)]
+    pub vault: VaultMetas<'info>,
+}
+
+fn main() {}
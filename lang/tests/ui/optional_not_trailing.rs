@@ -0,0 +1,11 @@
+use kaptn_lang::prelude::*;
+
+#[derive(ExtraMetas)]
+pub struct HookMetas<'info> {
+    #[meta(seeds = [literal(b"vault")])]
+    pub vault: Option<AccountInfo<'info>>,
+    #[meta(seeds = [literal(b"counter")])]
+    pub counter: AccountInfo<'info>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use kaptn_lang::prelude::*;
+
+#[derive(ExtraMetas)]
+pub struct HookMetas<'info> {
+    #[meta(pubkey = "Tokenz0DBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")]
+    pub token_program: AccountInfo<'info>,
+}
+
+fn main() {}
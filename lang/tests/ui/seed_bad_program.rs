@@ -0,0 +1,11 @@
+use kaptn_lang::prelude::*;
+
+#[derive(ExtraMetas)]
+pub struct HookMetas<'info> {
+    #[meta(seeds = [literal(b"vault")], program = token_program)]
+    pub vault: AccountInfo<'info>,
+    #[meta(pubkey = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")]
+    pub token_program: AccountInfo<'info>,
+}
+
+fn main() {}
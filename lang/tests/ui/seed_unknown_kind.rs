@@ -0,0 +1,9 @@
+use kaptn_lang::prelude::*;
+
+#[derive(ExtraMetas)]
+pub struct HookMetas<'info> {
+    #[meta(seeds = [literal(b"vault"), bump(source)])]
+    pub vault: AccountInfo<'info>,
+}
+
+fn main() {}
@@ -742,6 +742,207 @@ async fn fail_without_transferring_flag() {
     );
 }
 
+#[tokio::test]
+async fn fail_execute_incorrect_mint() {
+    let program_id = hello_world::ID;
+    let mut program_test = setup(&program_id);
+
+    let token_program_id = spl_token_2022::id();
+    let wallet = Keypair::new();
+    let mint_address = Pubkey::new_unique(); // wrong mint
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let decimals = 2;
+
+    setup_token_accounts(
+        &mut program_test,
+        &token_program_id,
+        &mint_address,
+        &mint_authority_pubkey,
+        &source,
+        &destination,
+        &wallet.pubkey(),
+        decimals,
+        true,
+    );
+
+    // initialize refuses foreign mints, so add a valid validation account by
+    // hand to make sure execute checks the mint on its own
+    let extra_account_metas_address = get_extra_account_metas_address(&mint_address, &program_id);
+    let mut validation_data = vec![0; ExtraAccountMetaList::size_of(0).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut validation_data, &[]).unwrap();
+    program_test.add_account(
+        extra_account_metas_address,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            data: validation_data,
+            owner: program_id,
+            ..SolanaAccount::default()
+        },
+    );
+
+    let extra_account_metas = [];
+    let mut context = program_test.start_with_context().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[execute_with_extra_account_metas(
+            &program_id,
+            &source,
+            &mint_address,
+            &destination,
+            &wallet.pubkey(),
+            &extra_account_metas_address,
+            &extra_account_metas,
+            0,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(KaptnError::InvalidMint as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_execute_accounts_not_owned_by_token_program() {
+    let program_id = hello_world::ID;
+    let mut program_test = setup(&program_id);
+
+    let wallet = Keypair::new();
+    let mint_address = hello_world::MINT;
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let decimals = 2;
+
+    setup_token_accounts(
+        &mut program_test,
+        &Pubkey::new_unique(), // not Token-2022
+        &mint_address,
+        &mint_authority_pubkey,
+        &source,
+        &destination,
+        &wallet.pubkey(),
+        decimals,
+        true,
+    );
+
+    let extra_account_metas_address = get_extra_account_metas_address(&mint_address, &program_id);
+    let extra_account_metas = [];
+    let mut context = program_test.start_with_context().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[execute_with_extra_account_metas(
+            &program_id,
+            &source,
+            &mint_address,
+            &destination,
+            &wallet.pubkey(),
+            &extra_account_metas_address,
+            &extra_account_metas,
+            0,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+    );
+}
+
+#[tokio::test]
+async fn fail_execute_token_account_of_other_mint() {
+    let program_id = hello_world::ID;
+    let mut program_test = setup(&program_id);
+
+    let token_program_id = spl_token_2022::id();
+    let wallet = Keypair::new();
+    let mint_address = hello_world::MINT;
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let decimals = 2;
+
+    setup_token_accounts(
+        &mut program_test,
+        &token_program_id,
+        &mint_address,
+        &mint_authority_pubkey,
+        &source,
+        &destination,
+        &wallet.pubkey(),
+        decimals,
+        true,
+    );
+
+    // a destination of another mint
+    let other_destination = Pubkey::new_unique();
+    setup_token_accounts(
+        &mut program_test,
+        &token_program_id,
+        &Pubkey::new_unique(),
+        &mint_authority_pubkey,
+        &Pubkey::new_unique(),
+        &other_destination,
+        &wallet.pubkey(),
+        decimals,
+        true,
+    );
+
+    let extra_account_metas_address = get_extra_account_metas_address(&mint_address, &program_id);
+    let extra_account_metas = [];
+    let mut context = program_test.start_with_context().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[execute_with_extra_account_metas(
+            &program_id,
+            &source,
+            &mint_address,
+            &other_destination,
+            &wallet.pubkey(),
+            &extra_account_metas_address,
+            &extra_account_metas,
+            0,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(KaptnError::TokenAccountMintMismatch as u32)
+        )
+    );
+}
+
 // #[tokio::test]
 // async fn success_on_chain_invoke_with_updated_extra_account_metas() {
 //     let hook_program_id = Pubkey::new_unique();